@@ -0,0 +1,288 @@
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
+use chrono::{DateTime, Utc};
+
+use crate::app::Arrival;
+
+// a train that vanished further out than this left the feed rather than arrived
+const ARRIVED_WITHIN_SECS: i32 = 90;
+// boards refreshed less often than this can't pin down when the train arrived
+pub const MAX_GAP_SECS: i64 = 120;
+// arrived trains kept for the summaries and export, oldest dropped first
+const KEEP_ARRIVED: usize = 5000;
+
+// one countdown as it was shown, for one train at one station
+#[derive(Debug, Clone)]
+pub struct Prediction {
+    pub seen_at: DateTime<Utc>,
+    pub expected: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackedTrain {
+    pub station_id: String,
+    pub station_name: String,
+    pub line_id: String,
+    pub vehicle_id: String,
+    pub predictions: Vec<Prediction>,
+    pub last_time_to_station: i32,
+}
+impl TrackedTrain {
+    fn last_seen(&self) -> DateTime<Utc> {
+        self.predictions.last().map(|p| p.seen_at).unwrap_or_else(Utc::now)
+    }
+}
+
+// a train that has left the board, with the moment it did so taken as its arrival
+#[derive(Debug, Clone)]
+pub struct ArrivedTrain {
+    pub train: TrackedTrain,
+    pub arrived_at: DateTime<Utc>,
+}
+impl ArrivedTrain {
+    // seconds the prediction was late (positive) or early (negative), with how far ahead it was made
+    pub fn errors(&self) -> Vec<(i64, i64)> {
+        self.train.predictions
+            .iter()
+            .map(|p| ((self.arrived_at - p.seen_at).num_seconds(), (self.arrived_at - p.expected).num_seconds()))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AccuracySummary {
+    pub name: String,
+    pub trains: usize,
+    pub predictions: usize,
+    pub mean_abs_error: f64,
+    pub bias: f64,
+    pub within_a_minute: f64,
+}
+impl AccuracySummary {
+    fn from_trains(name: String, trains: &[&ArrivedTrain]) -> AccuracySummary {
+        let errors = trains
+            .iter()
+            .flat_map(|t| t.errors())
+            .map(|(_, error)| error as f64)
+            .collect::<Vec<_>>();
+        let count = errors.len().max(1) as f64;
+        AccuracySummary {
+            name,
+            trains: trains.len(),
+            predictions: errors.len(),
+            mean_abs_error: errors.iter().map(|e| e.abs()).sum::<f64>() / count,
+            bias: errors.iter().sum::<f64>() / count,
+            within_a_minute: errors.iter().filter(|e| e.abs() <= 60.0).count() as f64 * 100.0 / count,
+        }
+    }
+}
+
+// every prediction seen on the boards, per vehicle, until the train arrives
+#[derive(Debug, Clone, Default)]
+pub struct PredictionLog {
+    pub tracked: BTreeMap<(String, String, String), TrackedTrain>,
+    pub arrived: Vec<ArrivedTrain>,
+    pub last_export: Option<String>,
+}
+impl PredictionLog {
+    // log a fresh board for a station; trains gone from it since the last board have arrived
+    pub fn record(&mut self, station_id: &str, arrivals: &[Arrival]) {
+        let now = Utc::now();
+        let mut on_board = Vec::new();
+        for arrival in arrivals.iter().filter(|a| !a.vehicleId.is_empty()) {
+            let expected = match DateTime::parse_from_rfc3339(&arrival.expectedArrival) {
+                Ok(d) => d.with_timezone(&Utc),
+                Err(_) => continue,
+            };
+            let key = (station_id.to_string(), arrival.lineId.clone(), arrival.vehicleId.clone());
+            let train = self.tracked.entry(key.clone()).or_insert_with(|| TrackedTrain {
+                station_id: station_id.to_string(),
+                station_name: arrival.stationName.clone(),
+                line_id: arrival.lineId.clone(),
+                vehicle_id: arrival.vehicleId.clone(),
+                predictions: Vec::new(),
+                last_time_to_station: arrival.timeToStation,
+            });
+            train.predictions.push(Prediction { seen_at: now, expected });
+            train.last_time_to_station = arrival.timeToStation;
+            on_board.push(key);
+        }
+
+        let gone = self.tracked
+            .keys()
+            .filter(|k| k.0 == station_id && !on_board.contains(k))
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in gone {
+            let train = self.tracked.remove(&key).unwrap();
+            if train.last_time_to_station <= ARRIVED_WITHIN_SECS && (now - train.last_seen()).num_seconds() <= MAX_GAP_SECS {
+                self.arrived.push(ArrivedTrain { train, arrived_at: now });
+            }
+        }
+        let excess = self.arrived.len().saturating_sub(KEEP_ARRIVED);
+        self.arrived.drain(..excess);
+
+        // trains at stations no longer being refreshed could only ever be thrown away; drop them now
+        self.tracked.retain(|_, t| (now - t.last_seen()).num_seconds() <= MAX_GAP_SECS);
+    }
+
+    pub fn by_line(&self) -> Vec<AccuracySummary> {
+        self.summarise(|t| t.train.line_id.clone())
+    }
+
+    pub fn by_station(&self) -> Vec<AccuracySummary> {
+        self.summarise(|t| t.train.station_name.trim_end_matches(" Underground Station").to_string())
+    }
+
+    fn summarise<F: Fn(&ArrivedTrain) -> String>(&self, key: F) -> Vec<AccuracySummary> {
+        let mut groups: BTreeMap<String, Vec<&ArrivedTrain>> = BTreeMap::new();
+        for train in &self.arrived {
+            groups.entry(key(train)).or_default().push(train);
+        }
+        groups
+            .into_iter()
+            .map(|(name, trains)| AccuracySummary::from_trains(name, &trains))
+            .collect()
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|d| d.join("ttfl").join("accuracy.csv"))
+    }
+
+    // one row per prediction of every arrived train
+    pub fn export(&mut self) -> io::Result<()> {
+        let path = PredictionLog::path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut csv = String::from("station_id,station_name,line_id,vehicle_id,seen_at,expected,arrived_at,seconds_ahead,error_seconds\n");
+        for train in &self.arrived {
+            for (prediction, (ahead, error)) in train.train.predictions.iter().zip(train.errors()) {
+                csv.push_str(&format!(
+                    "{},\"{}\",{},{},{},{},{},{},{}\n",
+                    train.train.station_id,
+                    train.train.station_name.replace('"', "\"\""),
+                    train.train.line_id,
+                    train.train.vehicle_id,
+                    prediction.seen_at.to_rfc3339(),
+                    prediction.expected.to_rfc3339(),
+                    train.arrived_at.to_rfc3339(),
+                    ahead,
+                    error,
+                ));
+            }
+        }
+        fs::write(&path, csv)?;
+        self.last_export = Some(format!("exported to {}", path.display()));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn arrival(vehicle_id: &str, time_to_station: i32) -> Arrival {
+        Arrival {
+            stationName: String::from("Brixton Underground Station"),
+            naptanId: String::from("940GZZLUBXN"),
+            lineId: String::from("victoria"),
+            platformName: String::from("Northbound - Platform 1"),
+            direction: String::from("outbound"),
+            destinationName: String::from("Walthamstow Central Underground Station"),
+            vehicleId: vehicle_id.to_string(),
+            modeName: String::from("tube"),
+            timeToStation: time_to_station,
+            currentLocation: String::new(),
+            expectedArrival: (Utc::now() + Duration::seconds(time_to_station as i64)).to_rfc3339(),
+            towards: String::new(),
+        }
+    }
+
+    #[test]
+    fn a_train_gone_from_the_board_close_in_has_arrived() {
+        let mut log = PredictionLog::default();
+        log.record("940GZZLUBXN", &[arrival("201", 150), arrival("202", 400)]);
+        log.record("940GZZLUBXN", &[arrival("201", 60), arrival("202", 310)]);
+        log.record("940GZZLUBXN", &[arrival("202", 220)]);
+
+        assert_eq!(log.arrived.len(), 1);
+        assert_eq!(log.arrived[0].train.vehicle_id, "201");
+        assert_eq!(log.arrived[0].train.predictions.len(), 2);
+        assert_eq!(log.tracked.len(), 1);
+    }
+
+    #[test]
+    fn a_train_gone_while_still_far_out_left_the_feed() {
+        let mut log = PredictionLog::default();
+        log.record("940GZZLUBXN", &[arrival("201", 600)]);
+        log.record("940GZZLUBXN", &[]);
+
+        assert!(log.arrived.is_empty());
+        assert!(log.tracked.is_empty());
+    }
+
+    #[test]
+    fn predictions_without_a_vehicle_are_ignored() {
+        let mut log = PredictionLog::default();
+        log.record("940GZZLUBXN", &[arrival("", 30)]);
+        assert!(log.tracked.is_empty());
+    }
+
+    #[test]
+    fn other_stations_boards_leave_a_train_alone() {
+        let mut log = PredictionLog::default();
+        log.record("940GZZLUBXN", &[arrival("201", 60)]);
+        log.record("940GZZLUSKW", &[]);
+        assert_eq!(log.tracked.len(), 1);
+        assert!(log.arrived.is_empty());
+    }
+
+    #[test]
+    fn trains_not_seen_for_too_long_are_dropped() {
+        let mut log = PredictionLog::default();
+        log.record("940GZZLUBXN", &[arrival("201", 60)]);
+        for train in log.tracked.values_mut() {
+            for prediction in &mut train.predictions {
+                prediction.seen_at = prediction.seen_at - Duration::seconds(MAX_GAP_SECS + 1);
+            }
+        }
+
+        // a board for another station is enough to clear it out
+        log.record("940GZZLUSKW", &[]);
+        assert!(log.tracked.is_empty());
+        assert!(log.arrived.is_empty());
+    }
+
+    #[test]
+    fn only_the_latest_arrivals_are_kept() {
+        let mut log = PredictionLog::default();
+        log.record("940GZZLUBXN", &[arrival("201", 60)]);
+        log.record("940GZZLUBXN", &[]);
+        log.arrived = vec![log.arrived[0].clone(); KEEP_ARRIVED];
+
+        log.record("940GZZLUBXN", &[arrival("202", 60)]);
+        log.record("940GZZLUBXN", &[]);
+        assert_eq!(log.arrived.len(), KEEP_ARRIVED);
+        assert_eq!(log.arrived.last().unwrap().train.vehicle_id, "202");
+        assert_eq!(log.arrived[0].train.vehicle_id, "201");
+    }
+
+    #[test]
+    fn errors_are_positive_when_the_train_was_late() {
+        let arrived_at = Utc::now();
+        let train = ArrivedTrain {
+            train: TrackedTrain {
+                station_id: String::from("940GZZLUBXN"),
+                station_name: String::from("Brixton Underground Station"),
+                line_id: String::from("victoria"),
+                vehicle_id: String::from("201"),
+                predictions: vec![Prediction { seen_at: arrived_at - Duration::seconds(300), expected: arrived_at - Duration::seconds(30) }],
+                last_time_to_station: 0,
+            },
+            arrived_at,
+        };
+        assert_eq!(train.errors(), vec![(300, 30)]);
+    }
+}
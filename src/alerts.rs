@@ -0,0 +1,51 @@
+use std::{io::{self, Write}, process::Stdio};
+use chrono::{DateTime, Local};
+use tokio::process::Command;
+
+use crate::config::Config;
+
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub line_name: String,
+    pub old_status: String,
+    pub new_status: String,
+    pub at: DateTime<Local>,
+    pub dismissed: bool,
+}
+impl Alert {
+    pub fn new(line_name: String, old_status: String, new_status: String) -> Alert {
+        Alert { line_name, old_status, new_status, at: Local::now(), dismissed: false }
+    }
+    // a problem to report rather than a status change; shown as "title: message"
+    pub fn notice(title: &str, message: String) -> Alert {
+        Alert::new(title.to_string(), message, String::new())
+    }
+}
+
+// ring the terminal bell and/or run the user's alert command, as configured
+pub fn notify(config: &Config, alert: &Alert) {
+    if config.bell {
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(b"\x07");
+        let _ = stdout.flush();
+    }
+
+    if let Some(cmd) = &config.alert_command {
+        // the command's output would corrupt the TUI; it is waited on in the background so it doesn't linger as a zombie
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(cmd)
+            .env("TTFL_LINE", &alert.line_name)
+            .env("TTFL_OLD_STATUS", &alert.old_status)
+            .env("TTFL_NEW_STATUS", &alert.new_status)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        if let Ok(mut child) = child {
+            tokio::spawn(async move {
+                let _ = child.wait().await;
+            });
+        }
+    }
+}
@@ -1,12 +1,23 @@
-use std::{io, collections::{HashMap, HashSet, BTreeMap, LinkedList}};
+use std::{io, collections::{HashMap, BTreeMap, BTreeSet, LinkedList}, time::{Duration, Instant}};
 use chrono::DateTime;
+use chrono_tz::Europe::London;
 // use rust_bert::pipelines::ner::NERModel;
 
 use crossterm::event::{self, Event, KeyCode};
 use serde_derive::{Serialize, Deserialize};
 use tui::{backend::Backend, Terminal, widgets::canvas::Rectangle, style::Color};
-use reqwest::{self, Client};
+use reqwest::{self, Client, Url};
+use tokio::sync::oneshot;
 
+use crate::accuracy::PredictionLog;
+use crate::alerts::{self, Alert};
+use crate::config::{Config, TimeFormat};
+use crate::diagram::{self, LANE_HEIGHT, NODE_WIDTH};
+use crate::network::NetworkMap;
+use crate::palette::{ColorMode, Palette};
+use crate::schedule::{ScheduledDirection, TimetableResponse};
+use crate::severity::{Severity, SeverityMeta};
+use crate::station_index::{AdditionalProperty, StationIndex};
 use crate::ui::ui;
 
 trait WithStationName {
@@ -16,9 +27,54 @@ pub enum InputMode {
     Normal,
     Insert,
 }
+#[derive(Clone, Copy, PartialEq)]
+pub enum DashboardSort {
+    Api,
+    Severity,
+    Name,
+    Favourites,
+}
+impl DashboardSort {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DashboardSort::Api => "API order",
+            DashboardSort::Severity => "worst first",
+            DashboardSort::Name => "by name",
+            DashboardSort::Favourites => "watched first",
+        }
+    }
+}
+#[derive(Clone, Copy, PartialEq)]
+pub enum BoardGrouping {
+    Platform,
+    Direction,
+    Destination,
+}
+impl BoardGrouping {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BoardGrouping::Platform => "platform",
+            BoardGrouping::Direction => "direction",
+            BoardGrouping::Destination => "destination",
+        }
+    }
+    pub fn next(&self) -> BoardGrouping {
+        match self {
+            BoardGrouping::Platform => BoardGrouping::Direction,
+            BoardGrouping::Direction => BoardGrouping::Destination,
+            BoardGrouping::Destination => BoardGrouping::Platform,
+        }
+    }
+}
+#[derive(Clone, Copy, PartialEq)]
+pub enum TimetableView {
+    Live,
+    Scheduled,
+}
 pub enum Focus {
     InputBlock,
-    LinesBlock
+    LinesBlock,
+    Map,
 }
 pub struct App<'a> {
     pub tab_titles: Vec<&'a str>,
@@ -27,36 +83,216 @@ pub struct App<'a> {
     pub input_mode: InputMode,
     pub lineNames: Vec<String>,
     pub lineData: Vec<Line>,
+    pub severities: BTreeMap<i32, String>,
+    pub dashboard_sort: DashboardSort,
+    pub disrupted_only: bool,
     pub focus: Option<Focus>,
     pub line_selected: Option<usize>,
     pub lines_tree_size: Option<usize>,
     pub this_station_name: String,
     pub this_StopTimetable: StopTimetable,
+    pub this_LineDetail: Option<LineDetail>,
+    pub board_grouping: BoardGrouping,
+    pub timetable_view: TimetableView,
+    pub timetable_scroll: usize,
+    pub platform_scroll: usize,
+    // the selected train as (line id, train key), so it stays selected as the board refreshes
+    pub arrival_selected: Option<(String, String)>,
+    pub map_zoom: f64,
+    pub map_pan: f64,
+    // the range of map_pan that moves the diagrams, set each time they are drawn
+    pub map_pan_limits: (f64, f64),
+    pub map_cursor: Option<usize>,
+    pub geo_zoom: f64,
+    pub geo_pan: (f64, f64),
+    // the range of each part of geo_pan that keeps stations in the middle of the map, set each time it is drawn
+    pub geo_pan_limits: ((f64, f64), (f64, f64)),
+    pub followed: Option<FollowedTrain>,
     pub api_client: Option<Client>,
     pub line_cache: BTreeMap<String, Vec<String>>,
     pub stop_cache: BTreeMap<String, StopTimetable>,
+    pub station_info: BTreeMap<String, StationInfo>,
+    pub network: NetworkMap,
+    pub line_feeds: BTreeMap<String, LineFeed>,
+    pub station_index: StationIndex,
+    // the index is read or built on its own task, as fetching every stop in the enabled modes can take minutes
+    pub station_index_build: Option<oneshot::Receiver<Option<StationIndex>>>,
+    // when the last build failed, so it is tried again once INDEX_RETRY_SECS have passed
+    pub station_index_failed_at: Option<Instant>,
+    pub suggestions: Vec<StopPoint>,
+    pub suggestion_selected: Option<usize>,
+    pub input_changed_at: Option<Instant>,
+    pub config: Config,
+    pub palette: Palette,
+    pub alerts: Vec<Alert>,
+    pub accuracy: PredictionLog,
+    pub last_refresh: Instant,
 }
 impl<'a> App<'a> {
     pub fn new() -> App<'a> {
+        let config = Config::load();
+        let alerts = config.unreadable
+            .iter()
+            .map(|e| Alert::notice("Settings not loaded", format!("{}; fix the file, changes won't be saved until then", e)))
+            .collect();
         App {
-            tab_titles: vec!["Line Status", "Timetable"],
+            tab_titles: vec!["Line Status", "Timetable", "Lines", "Watch", "Accuracy", "Network", "Map"],
             tab_index: 0,
             input: String::new(),
             input_mode: InputMode::Normal,
             lineNames: Vec::new(),
             lineData: Vec::new(),
+            severities: BTreeMap::new(),
+            dashboard_sort: DashboardSort::Api,
+            disrupted_only: false,
             focus: None,
             line_selected: Some(0),
             lines_tree_size: Some(0),
             this_station_name: String::new(),
             this_StopTimetable: StopTimetable::default(),
+            this_LineDetail: None,
+            board_grouping: BoardGrouping::Platform,
+            timetable_view: TimetableView::Live,
+            timetable_scroll: 0,
+            platform_scroll: 0,
+            arrival_selected: None,
+            map_zoom: 1.0,
+            map_pan: 0.0,
+            map_pan_limits: (0.0, 0.0),
+            map_cursor: None,
+            geo_zoom: 1.0,
+            geo_pan: (0.0, 0.0),
+            geo_pan_limits: ((0.0, 0.0), (0.0, 0.0)),
+            followed: None,
             api_client: None,
             line_cache: BTreeMap::new(),
-            stop_cache: BTreeMap::new()
+            stop_cache: BTreeMap::new(),
+            station_info: BTreeMap::new(),
+            network: NetworkMap::default(),
+            line_feeds: BTreeMap::new(),
+            station_index: StationIndex::default(),
+            station_index_build: None,
+            station_index_failed_at: None,
+            suggestions: Vec::new(),
+            suggestion_selected: None,
+            input_changed_at: None,
+            config,
+            palette: Palette::new(ColorMode::Auto),
+            alerts,
+            accuracy: PredictionLog::default(),
+            last_refresh: Instant::now(),
         }
     }
     pub fn next(&mut self) {
         self.tab_index = (self.tab_index + 1) % self.tab_titles.len();
+        self.leave_map();
+    }
+    // the map cursor only exists on the station tab, so switching tabs gives the keys back
+    fn leave_map(&mut self) {
+        if matches!(self.focus, Some(Focus::Map)) {
+            self.focus = None;
+        }
+    }
+    // lines for the dashboard, filtered and sorted as selected
+    pub fn dashboard_lines(&self) -> Vec<Line> {
+        let mut lines = self.lineData
+            .iter()
+            .filter(|l| !self.disrupted_only || l.worst_severity().is_disrupted())
+            .cloned()
+            .collect::<Vec<_>>();
+        match self.dashboard_sort {
+            DashboardSort::Api => {}
+            DashboardSort::Severity => lines.sort_by_key(|l| l.worst_severity()),
+            DashboardSort::Name => lines.sort_by(|a, b| a.name.cmp(&b.name)),
+            DashboardSort::Favourites => lines.sort_by_key(|l| !self.config.is_watched(&l.id)),
+        }
+        lines
+    }
+    pub fn toggle_sort(&mut self, sort: DashboardSort) {
+        self.dashboard_sort = match self.dashboard_sort == sort {
+            true => DashboardSort::Api,
+            false => sort,
+        };
+    }
+    // alerts are shown oldest first, so several raised by one refresh are each seen in turn
+    pub fn active_alert(&self) -> Option<&Alert> {
+        self.alerts.iter().find(|a| !a.dismissed)
+    }
+    pub fn waiting_alerts(&self) -> usize {
+        self.alerts.iter().filter(|a| !a.dismissed).count().saturating_sub(1)
+    }
+    pub fn dismiss_alert(&mut self) {
+        if let Some(alert) = self.alerts.iter_mut().find(|a| !a.dismissed) {
+            alert.dismissed = true;
+        }
+    }
+    // the line whose diagram the map cursor moves along: the top one on the board
+    pub fn cursor_line(&self) -> Option<&String> {
+        let lines = &self.this_StopTimetable.unique_lines;
+        lines.iter().nth(self.timetable_scroll.min(lines.len().saturating_sub(1)))
+    }
+    pub fn cursor_station(&self) -> Option<&Station> {
+        let live_map = self.this_StopTimetable.live_maps.get(self.cursor_line()?)?;
+        live_map.stations.get(self.map_cursor?)
+    }
+    // step the map cursor left or right through the stations, in column order
+    pub fn move_map_cursor(&mut self, step: i32) {
+        let live_map = match self.cursor_line().and_then(|l| self.this_StopTimetable.live_maps.get(l)) {
+            Some(m) if !m.stations.is_empty() => m,
+            _ => return,
+        };
+        let mut order = (0..live_map.stations.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| {
+            let (a, b) = (&live_map.stations[a], &live_map.stations[b]);
+            a.column.partial_cmp(&b.column).unwrap_or(std::cmp::Ordering::Equal).then(a.lane.cmp(&b.lane))
+        });
+        let at = self.map_cursor.and_then(|c| order.iter().position(|&i| i == c)).unwrap_or(0) as i32;
+        let to = (at + step).max(0).min(order.len() as i32 - 1) as usize;
+        self.map_cursor = Some(order[to]);
+    }
+    // put the cursor on the current station of the top line, or its first station
+    pub fn focus_map(&mut self) {
+        let current = self.this_StopTimetable.stop_point.as_ref().map(|s| s.id.clone()).unwrap_or_default();
+        let cursor = match self.cursor_line().and_then(|l| self.this_StopTimetable.live_maps.get(l)) {
+            Some(m) if !m.stations.is_empty() => m.station_index(&current).or(Some(0)),
+            _ => None,
+        };
+        if cursor.is_some() {
+            self.map_cursor = cursor;
+            self.focus = Some(Focus::Map);
+        }
+    }
+    // pan the line diagrams, never further than they can move, so panning back takes effect at once
+    pub fn pan_map(&mut self, step: f64) {
+        let (least, most) = self.map_pan_limits;
+        self.map_pan = (self.map_pan.clamp(least, most) + step).clamp(least, most);
+    }
+    pub fn pan_geo(&mut self, x: f64, y: f64) {
+        let ((x0, x1), (y0, y1)) = self.geo_pan_limits;
+        self.geo_pan = (
+            (self.geo_pan.0.clamp(x0, x1) + x).clamp(x0, x1),
+            (self.geo_pan.1.clamp(y0, y1) + y).clamp(y0, y1),
+        );
+    }
+    pub fn selected_arrival(&self) -> Option<&Arrival> {
+        let (line, train) = self.arrival_selected.as_ref()?;
+        self.this_StopTimetable.arrivals
+            .iter()
+            .find(|a| a.lineId == *line && a.train_key() == *train)
+    }
+    // step through the trains on the boards; stepping up past the first clears the selection
+    pub fn move_arrival_selection(&mut self, step: i32) {
+        let arrivals = self.this_StopTimetable.followable_arrivals(self.board_grouping);
+        let current = self.selected_arrival().and_then(|s| arrivals.iter().position(|a| std::ptr::eq(*a, s)));
+        let next = match (current, step > 0) {
+            (Some(i), true) => Some((i + 1).min(arrivals.len().saturating_sub(1))),
+            (None, true) => Some(0),
+            (Some(i), false) => i.checked_sub(1),
+            (None, false) => None,
+        };
+        self.arrival_selected = next
+            .and_then(|i| arrivals.get(i))
+            .map(|a| (a.lineId.clone(), a.train_key()));
     }
     pub fn previous(&mut self) {
         if self.tab_index > 0 {
@@ -64,6 +300,7 @@ impl<'a> App<'a> {
         } else {
             self.tab_index = self.tab_titles.len() - 1;
         }
+        self.leave_map();
     }
 }
 
@@ -72,21 +309,69 @@ pub struct LineStatus {
     pub id: i32,
     pub statusSeverity: i32,
     pub statusSeverityDescription: String,
-    pub reason: Option<String>
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub validityPeriods: Vec<ValidityPeriod>,
+    #[serde(default)]
+    pub disruption: Option<StatusDisruption>,
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatusDisruption {
+    #[serde(default)]
+    pub affectedStops: Vec<AffectedStop>,
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AffectedStop {
+    #[serde(default)]
+    pub naptanId: String,
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ValidityPeriod {
+    pub fromDate: String,
+    pub toDate: String,
+    #[serde(default)]
+    pub isNow: bool,
+}
+impl ValidityPeriod {
+    pub fn window(&self) -> String {
+        let format = |date: &str| match DateTime::parse_from_rfc3339(date) {
+            Ok(d) => d.with_timezone(&London).format("%a %d %b %H:%M").to_string(),
+            Err(_) => date.to_string(),
+        };
+        format!("{} - {}", format(&self.fromDate), format(&self.toDate))
+    }
+}
+impl LineStatus {
+    pub fn severity(&self) -> Severity {
+        Severity::from_level(self.statusSeverity)
+    }
 }
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Disruption {
-    category: String,
-    categoryDescription: String,
-    description: String,
-    summary: String,
-    additionalInfo: String,
+    pub category: String,
+    pub categoryDescription: String,
+    pub description: String,
+    #[serde(default)]
+    pub summary: String,
+    #[serde(default)]
+    pub additionalInfo: String,
 }
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StopPoint {
+    #[serde(default)]
     pub zone: String,
     pub id: String,
     pub name: String,
+    #[serde(default)]
+    pub modes: Vec<String>,
+    #[serde(default)]
+    pub stopLetter: String,
+    #[serde(default)]
+    pub towards: String,
+    #[serde(default)]
+    pub lat: f64,
+    #[serde(default)]
+    pub lon: f64,
 }
 impl Default for StopPoint {
     fn default() -> StopPoint {
@@ -94,23 +379,178 @@ impl Default for StopPoint {
             zone: String::new(),
             id: String::new(),
             name: String::new(),
+            modes: Vec::new(),
+            stopLetter: String::new(),
+            towards: String::new(),
+            lat: 0.0,
+            lon: 0.0,
         }
     }
 }
-#[derive(Clone)]
+impl StopPoint {
+    pub fn is_bus(&self) -> bool {
+        self.modes.iter().any(|m| m == "bus")
+    }
+    // "Brixton Station (Stop K, towards Streatham)"
+    pub fn label(&self) -> String {
+        match (self.stopLetter.is_empty(), self.towards.is_empty()) {
+            (true, true) => self.name.clone(),
+            (false, true) => format!("{} (Stop {})", self.name, self.stopLetter),
+            (true, false) => format!("{} (towards {})", self.name, self.towards),
+            (false, false) => format!("{} (Stop {}, towards {})", self.name, self.stopLetter, self.towards),
+        }
+    }
+}
+// StopPoint/{id}, used to find the individual stops behind a bus stop area
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StopPointDetail {
+    pub naptanId: String,
+    pub commonName: String,
+    #[serde(default)]
+    pub stopType: String,
+    #[serde(default)]
+    pub stopLetter: Option<String>,
+    #[serde(default)]
+    pub additionalProperties: Vec<AdditionalProperty>,
+    #[serde(default)]
+    pub lat: f64,
+    #[serde(default)]
+    pub lon: f64,
+    #[serde(default)]
+    pub children: Vec<StopPointDetail>,
+    #[serde(default)]
+    pub lines: Vec<LineIdentifier>,
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LineIdentifier {
+    pub id: String,
+    pub name: String,
+}
+// StopPoint/{ids} answers a single id with an object and several with a list
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum OneOrMany<T> {
+    Many(Vec<T>),
+    One(T),
+}
+impl<T> OneOrMany<T> {
+    pub fn into_vec(self) -> Vec<T> {
+        match self {
+            OneOrMany::Many(v) => v,
+            OneOrMany::One(t) => vec![t],
+        }
+    }
+}
+// what the line diagrams need to know about a station
+#[derive(Debug, Clone)]
+pub struct StationInfo {
+    pub name: String,
+    pub zone: String,
+    pub lines: Vec<String>,
+}
+impl StopPointDetail {
+    // the bus stops themselves, at any depth
+    pub fn bus_stops(&self) -> Vec<StopPoint> {
+        match self.stopType.as_str() {
+            "NaptanPublicBusCoachTram" => vec![StopPoint {
+                zone: String::new(),
+                id: self.naptanId.clone(),
+                name: self.commonName.clone(),
+                modes: vec![String::from("bus")],
+                stopLetter: self.stopLetter.clone().unwrap_or_default(),
+                towards: self.additionalProperties
+                    .iter()
+                    .find(|p| p.key == "Towards")
+                    .map(|p| p.value.clone())
+                    .unwrap_or_default(),
+                lat: self.lat,
+                lon: self.lon,
+            }],
+            _ => self.children.iter().flat_map(|c| c.bus_stops()).collect(),
+        }
+    }
+}
+#[derive(Clone, Default)]
 pub struct StopTimetable {
     pub stop_point: Option<StopPoint>,
-    pub unique_lines: HashSet<String>,
+    pub unique_lines: BTreeSet<String>,
     pub unique_platforms: HashMap<String, Vec<String>>,
     pub arrivals: Vec<Arrival>,
     pub live_maps: BTreeMap<String, LiveMap>,
-    pub station_nodes: BTreeMap<String, Vec<Vec<StationNode>>>,
+    pub station_nodes: BTreeMap<String, Vec<StationNode>>,
+    pub schedules: BTreeMap<String, Vec<ScheduledDirection>>,
+    pub refreshed_at: Option<Instant>,
+    pub bus_stops: Vec<StopPoint>,
 }
-impl Default for StopTimetable {
-    fn default() -> StopTimetable {
-        StopTimetable { stop_point: None, unique_lines: HashSet::new(), unique_platforms: HashMap::new(), arrivals: Vec::new(), live_maps: BTreeMap::new(), station_nodes: BTreeMap::new() }
+impl StopTimetable {
+    // replace the arrivals along with the lines and platforms taken from them,
+    // so lines that start or stop running gain or lose their row
+    pub fn set_arrivals(&mut self, arrivals: Vec<Arrival>) {
+        self.arrivals = arrivals;
+        self.unique_lines = self.arrivals.iter().map(|a| a.lineId.clone()).collect();
+        // platforms (stop letters, for buses) by line, sorted
+        self.unique_platforms = self.unique_lines
+            .iter()
+            .map(|line| {
+                let platforms = self.arrivals
+                    .iter()
+                    .filter(|a| a.lineId == *line)
+                    .map(|a| a.platformName.clone())
+                    .collect::<BTreeSet<_>>();
+                (line.clone(), platforms.into_iter().collect())
+            })
+            .collect();
+    }
+    // board columns for a line under the chosen grouping
+    pub fn board_groups(&self, line: &str, grouping: BoardGrouping) -> Vec<String> {
+        match grouping {
+            BoardGrouping::Platform => self.unique_platforms.get(line).cloned().unwrap_or_default(),
+            _ => self.arrivals
+                .iter()
+                .filter(|a| a.lineId == line)
+                .map(|a| a.board_group(grouping))
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect(),
+        }
+    }
+    // bus stops are grouped by stop letter, shown with the direction they serve
+    pub fn group_title(&self, group: &str, grouping: BoardGrouping) -> String {
+        let stop = self.bus_stops.iter().find(|s| s.stopLetter == group);
+        match (grouping, stop) {
+            (BoardGrouping::Platform, Some(s)) if !s.towards.is_empty() => format!("Stop {} towards {}", s.stopLetter, s.towards),
+            (BoardGrouping::Platform, Some(s)) => format!("Stop {}", s.stopLetter),
+            _ => group.to_string(),
+        }
+    }
+    pub fn is_bus_route(&self, line: &str) -> bool {
+        self.arrivals.iter().any(|a| a.lineId == line && a.modeName == "bus")
+    }
+    // trains with a vehicle id, in the order the boards show them: by line, then board, then time
+    pub fn followable_arrivals(&self, grouping: BoardGrouping) -> Vec<&Arrival> {
+        let mut arrivals = Vec::new();
+        for line in &self.unique_lines {
+            for group in self.board_groups(line, grouping) {
+                let mut board = self.arrivals
+                    .iter()
+                    .filter(|a| !a.vehicleId.is_empty() && a.lineId == *line && a.board_group(grouping) == group)
+                    .collect::<Vec<_>>();
+                board.sort_by_key(|a| a.timeToStation);
+                arrivals.extend(board);
+            }
+        }
+        arrivals
     }
 }
+// a train picked on the board, tracked by vehicle id as it moves along the line
+#[derive(Debug, Clone)]
+pub struct FollowedTrain {
+    pub vehicle_id: String,
+    pub line_id: String,
+    pub destination: String,
+    pub stops: Vec<Arrival>,
+    pub refreshed_at: Instant,
+}
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Line {
     pub id: String,
@@ -119,6 +559,57 @@ pub struct Line {
     pub disruptions: Vec<Disruption>,
     pub lineStatuses: Vec<Option<LineStatus>>,
 }
+impl Line {
+    pub fn worst_status(&self) -> Option<&LineStatus> {
+        self.lineStatuses.iter().flatten().min_by_key(|s| s.severity())
+    }
+    pub fn worst_severity(&self) -> Severity {
+        self.worst_status()
+            .map(|s| s.severity())
+            .unwrap_or(Severity::GoodService)
+    }
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LineStop {
+    pub naptanId: String,
+    pub commonName: String,
+}
+#[derive(Clone)]
+pub struct LineDetail {
+    pub line: Line,
+    pub stations: Vec<LineStop>,
+    pub disruptions: Vec<Disruption>,
+}
+// every prediction on a line from Line/{id}/Arrivals, shared by the line view and the live maps
+#[derive(Debug, Clone)]
+pub struct LineFeed {
+    pub arrivals: Vec<Arrival>,
+    pub refreshed_at: Instant,
+    // the last request failed, so arrivals are from an earlier one, or empty
+    pub failed: bool,
+}
+impl LineFeed {
+    // one entry per train, its nearest prediction: the stop it is heading for next
+    pub fn trains(&self) -> Vec<&Arrival> {
+        let mut trains: BTreeMap<String, &Arrival> = BTreeMap::new();
+        for arrival in &self.arrivals {
+            let train = trains.entry(arrival.train_key()).or_insert(arrival);
+            if arrival.timeToStation < train.timeToStation {
+                *train = arrival;
+            }
+        }
+        let mut trains = trains.into_values().collect::<Vec<_>>();
+        trains.sort_by_key(|a| a.timeToStation);
+        trains
+    }
+    pub fn by_direction(&self) -> BTreeMap<String, Vec<&Arrival>> {
+        let mut directions: BTreeMap<String, Vec<&Arrival>> = BTreeMap::new();
+        for train in self.trains() {
+            directions.entry(train.board_group(BoardGrouping::Direction)).or_default().push(train);
+        }
+        directions
+    }
+}
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StopPointResponse {
     pub query: String,
@@ -132,20 +623,90 @@ pub struct ArrivalsResponse {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Arrival {
     pub stationName: String,
+    #[serde(default)]
+    pub naptanId: String,
     pub lineId: String,
     pub platformName: String,
-    // pub direction: String,
-    // pub destinationName: String,
+    #[serde(default)]
+    pub direction: String,
+    #[serde(default)]
+    pub destinationName: String,
+    #[serde(default)]
+    pub vehicleId: String,
+    #[serde(default)]
+    pub modeName: String,
     pub timeToStation: i32,
+    #[serde(default)]
     pub currentLocation: String,
     pub expectedArrival: String,
+    #[serde(default)]
     pub towards: String
 }
+impl Arrival {
+    // destinationName is missing on some predictions; towards is the next best thing
+    pub fn destination(&self) -> String {
+        match self.destinationName.is_empty() {
+            true => self.towards.clone(),
+            false => self.destinationName.clone(),
+        }
+    }
+    pub fn board_group(&self, grouping: BoardGrouping) -> String {
+        match grouping {
+            BoardGrouping::Platform => self.platformName.clone(),
+            BoardGrouping::Direction => {
+                let mut chars = self.direction.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => "Unknown".to_string(),
+                }
+            }
+            BoardGrouping::Destination => self.destination(),
+        }
+    }
+    pub fn countdown(&self) -> String {
+        match self.timeToStation / 60 {
+            0 => "Due".to_string(),
+            1 => "1 min".to_string(),
+            m => format!("{} mins", m),
+        }
+    }
+    // expected arrival as London wall-clock time
+    pub fn clock(&self) -> Option<String> {
+        DateTime::parse_from_rfc3339(&self.expectedArrival)
+            .ok()
+            .map(|d| d.with_timezone(&London).format("%H:%M").to_string())
+    }
+    pub fn display_time(&self, format: TimeFormat) -> String {
+        match (format, self.clock()) {
+            (TimeFormat::Countdown, _) | (_, None) => self.countdown(),
+            (TimeFormat::Clock, Some(clock)) => clock,
+            (TimeFormat::Both, Some(clock)) => format!("{} {}", clock, self.countdown()),
+        }
+    }
+    // identifies the train behind a prediction
+    pub fn train_key(&self) -> String {
+        match self.vehicleId.is_empty() {
+            true => self.currentLocation.clone(),
+            false => self.vehicleId.clone(),
+        }
+    }
+}
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RouteResponse {
     pub lineId: String,
     pub direction: String,
-    pub orderedLineRoutes: Vec<Route>
+    pub orderedLineRoutes: Vec<Route>,
+    #[serde(default)]
+    pub stations: Vec<RouteStation>,
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RouteStation {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub lat: f64,
+    #[serde(default)]
+    pub lon: f64,
 }
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Route {
@@ -155,38 +716,765 @@ pub struct Route {
 #[derive(Clone)]
 pub struct Station {
     pub naptan_id: String,
+    pub name: String,
+    pub interchange: bool,
+    pub column: f64,
+    pub lane: i32,
 }
 impl WithStationName for Station {
     fn new(stop_name: String) -> Self {
-        Station { naptan_id: stop_name }
+        Station { naptan_id: stop_name, name: String::new(), interchange: false, column: 0.0, lane: 0 }
     }
 }
 pub struct Link {
     pub link_name: String,
     pub is_current: bool
 }
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct LiveMap {
-    pub stops_0: Vec<Station>,
+    pub stations: Vec<Station>,
     // pub links: LinkedList<Link>,
-    pub stops_1: Vec<Station>,
-    pub trains_currently_at: Vec<String>
+    // every route sequence, as indexes into stations running the same way along the line
+    pub routes: Vec<Vec<usize>>,
+    pub edges: Vec<(usize, usize)>,
+    pub trains_currently_at: Vec<TrainMarker>
 }
-impl Default for LiveMap {
-    fn default() -> LiveMap {
-        LiveMap { stops_0: Vec::new(), stops_1: Vec::new(), trains_currently_at: Vec::new() }
+impl LiveMap {
+    pub fn from_routes(routes: &[Route]) -> LiveMap {
+        let layout = diagram::layout(&routes.iter().map(|r| r.naptanIds.clone()).collect::<Vec<_>>());
+        LiveMap {
+            stations: layout.stops
+                .into_iter()
+                .map(|s| Station { column: s.column, lane: s.lane, ..Station::new(s.naptan_id) })
+                .collect(),
+            routes: layout.routes,
+            edges: layout.edges,
+            trains_currently_at: Vec::new(),
+        }
+    }
+    pub fn lanes(&self) -> (i32, i32) {
+        let lanes = self.stations.iter().map(|s| s.lane);
+        (lanes.clone().min().unwrap_or(0), lanes.max().unwrap_or(0))
+    }
+    pub fn station_index(&self, naptan_id: &str) -> Option<usize> {
+        self.stations.iter().position(|s| s.naptan_id == naptan_id)
+    }
+    // place every train on the line from its predictions, soonest first
+    pub fn place_trains(&mut self, line_arrivals: &[Arrival]) {
+        let mut by_train: BTreeMap<String, Vec<&Arrival>> = BTreeMap::new();
+        for arrival in line_arrivals {
+            by_train.entry(arrival.train_key()).or_default().push(arrival);
+        }
+
+        let mut markers = Vec::new();
+        for (key, mut predictions) in by_train {
+            predictions.sort_by_key(|a| a.timeToStation);
+            let next = match self.station_index(&predictions[0].naptanId) {
+                Some(i) => i,
+                None => continue,
+            };
+            let after = predictions.get(1).and_then(|a| self.station_index(&a.naptanId));
+
+            // the route the train is on, and which way along it, from where its next two stops come
+            let mut found = None;
+            for route in &self.routes {
+                let p = match route.iter().position(|&s| s == next) {
+                    Some(p) => p,
+                    None => continue,
+                };
+                match after.and_then(|a| route.iter().position(|&s| s == a)) {
+                    Some(q) => {
+                        found = Some((route, p, q > p));
+                        break;
+                    }
+                    None if found.is_none() => found = Some((route, p, true)),
+                    None => {}
+                }
+            }
+            let (route, p, forward) = match found {
+                Some(f) => f,
+                None => continue,
+            };
+            let previous = match forward {
+                true => p.checked_sub(1).map(|q| route[q]),
+                false => route.get(p + 1).copied(),
+            };
+
+            // "At Oxford Circus" means at the next stop; otherwise somewhere on the way to it
+            let time_to_station = predictions[0].timeToStation;
+            let at_station = time_to_station <= 30 || predictions[0].currentLocation.starts_with("At ");
+            let (from, progress) = match (at_station, previous) {
+                (false, Some(previous)) => (previous, 1.0 - (time_to_station as f64 / SECS_BETWEEN_STATIONS).min(1.0)),
+                _ => (next, 1.0),
+            };
+            markers.push(TrainMarker {
+                train_key: key,
+                from,
+                to: next,
+                progress,
+                forward,
+                at_station,
+            });
+        }
+        self.trains_currently_at = markers;
     }
 }
+// how far the line diagrams zoom in, and how many stations a pan moves them
+const MAX_MAP_ZOOM: f64 = 8.0;
+const MAP_PAN_STEP: f64 = 3.0;
+const MAX_GEO_ZOOM: f64 = 40.0;
+const GEO_PAN_STEP: f64 = 0.25;
+// rough time between neighbouring stations, for placing trains between them
+const INDEX_RETRY_SECS: u64 = 60;
+const SECS_BETWEEN_STATIONS: f64 = 120.0;
+// a train on the live map, progress of the way from one station to the next
+#[derive(Debug, Clone)]
+pub struct TrainMarker {
+    pub train_key: String,
+    pub from: usize,
+    pub to: usize,
+    pub progress: f64,
+    pub forward: bool,
+    pub at_station: bool,
+}
 #[derive(Clone)]
 pub struct StationNode {
     pub naptan_id: String,
     pub rect: Rectangle,
 }
 
+// severity descriptions for tube, falling back to the built-in ones
+async fn load_severities(app: &mut App<'_>) {
+    if let Ok(res) = app.api_client.as_ref().unwrap().get("https://api.tfl.gov.uk/Line/Meta/Severity").send().await {
+        if let Ok(meta) = res.json::<Vec<SeverityMeta>>().await {
+            for m in meta.iter().filter(|m| m.modeName == "tube") {
+                app.severities.insert(m.severityLevel, m.description.clone());
+            }
+        }
+    }
+    if app.severities.is_empty() {
+        for s in Severity::ALL {
+            app.severities.insert(s.level(), s.description());
+        }
+    }
+}
+
+// re-fetch line statuses and raise an alert for every watched line whose severity changed
+async fn refresh_line_status(app: &mut App<'_>) {
+    app.last_refresh = Instant::now();
+    let result = match app.api_client.as_ref().unwrap().get("https://api.tfl.gov.uk/line/mode/tube/status").send().await {
+        Ok(res) => match res.json::<Vec<Line>>().await {
+            Ok(lines) => lines,
+            Err(_) => return,
+        },
+        Err(_) => return,
+    };
+
+    for line in &result {
+        if !app.config.is_watched(&line.id) {
+            continue;
+        }
+        let old = match app.lineData.iter().find(|l| l.id == line.id) {
+            Some(l) => l.worst_status(),
+            None => continue,
+        };
+        let new = line.worst_status();
+        if let (Some(old), Some(new)) = (old, new) {
+            if old.statusSeverity != new.statusSeverity {
+                let alert = Alert::new(
+                    line.name.clone(),
+                    old.statusSeverityDescription.clone(),
+                    new.statusSeverityDescription.clone(),
+                );
+                alerts::notify(&app.config, &alert);
+                app.alerts.push(alert);
+            }
+        }
+    }
+
+    app.lineNames = app.line_cache["lineNames"].clone();
+    app.lineData = result;
+}
+
+// periodic work, run on every pass of the event loop
+async fn on_tick(app: &mut App<'_>) {
+    poll_station_index(app);
+
+    // search once typing has paused
+    if let Some(changed) = app.input_changed_at {
+        if changed.elapsed() >= Duration::from_millis(300) {
+            app.input_changed_at = None;
+            app.suggestion_selected = None;
+            app.suggestions = match app.input.chars().count() {
+                0 | 1 => Vec::new(),
+                _ => {
+                    let query = app.input.clone();
+                    search_stop_points(app, &query).await
+                }
+            };
+        }
+    }
+
+    if app.last_refresh.elapsed() >= Duration::from_secs(app.config.refresh_secs) {
+        refresh_line_status(app).await;
+    }
+    refresh_pinned(app).await;
+    refresh_open_board(app).await;
+    refresh_followed(app).await;
+    refresh_line_feeds(app).await;
+
+    // the network map is only fetched once its tab, or the geographic map, is opened
+    if (app.tab_index == 5 || app.tab_index == 6) && !app.network.attempted {
+        let line_ids = app.lineData.iter().map(|l| l.id.clone()).collect::<Vec<_>>();
+        app.network = NetworkMap::load(app.api_client.as_ref().unwrap(), &line_ids).await;
+    }
+}
+
+// start reading or building the station index in the background, and pick it up once it is done
+fn poll_station_index(app: &mut App<'_>) {
+    if let Some(build) = app.station_index_build.as_mut() {
+        match build.try_recv() {
+            Err(oneshot::error::TryRecvError::Empty) => return,
+            Ok(Some(index)) if !index.is_empty() => {
+                app.station_index = index;
+                app.station_index_failed_at = None;
+            }
+            _ => app.station_index_failed_at = Some(Instant::now()),
+        }
+        app.station_index_build = None;
+        return;
+    }
+    let due = app.station_index_failed_at.is_none_or(|t| t.elapsed() >= Duration::from_secs(INDEX_RETRY_SECS));
+    if app.station_index.is_empty() && due {
+        let (tx, rx) = oneshot::channel();
+        let client = app.api_client.clone().unwrap();
+        let modes = app.config.modes.clone();
+        tokio::spawn(async move {
+            let _ = tx.send(StationIndex::load_or_build(&client, &modes).await);
+        });
+        app.station_index_build = Some(rx);
+    }
+}
+
+// scheduled first/last trains for every line at the current station, one entry per terminus it runs to
+async fn load_schedules(app: &mut App<'_>) {
+    let stop_id = match &app.this_StopTimetable.stop_point {
+        Some(s) => s.id.clone(),
+        None => return,
+    };
+    let client = app.api_client.as_ref().unwrap();
+
+    let mut schedules = BTreeMap::new();
+    for (line, live_map) in &app.this_StopTimetable.live_maps {
+        // both ends of every route the station is on, short of the station itself
+        let mut termini = BTreeSet::new();
+        for route in &live_map.routes {
+            if let Some(p) = route.iter().position(|&s| live_map.stations[s].naptan_id == stop_id) {
+                if p + 1 < route.len() {
+                    termini.insert(live_map.stations[route[route.len() - 1]].naptan_id.clone());
+                }
+                if p > 0 {
+                    termini.insert(live_map.stations[route[0]].naptan_id.clone());
+                }
+            }
+        }
+
+        let mut directions = Vec::new();
+        for terminus in termini {
+            let response = match client.get(format!("https://api.tfl.gov.uk/Line/{}/Timetable/{}/to/{}", line, stop_id, terminus)).send().await {
+                Ok(res) => match res.json::<TimetableResponse>().await {
+                    Ok(t) => t,
+                    Err(_) => continue,
+                },
+                Err(_) => continue,
+            };
+            let towards = response.stations
+                .iter()
+                .find(|s| s.id == terminus)
+                .map(|s| s.name.clone())
+                .unwrap_or(terminus);
+            if let Some(direction) = ScheduledDirection::from_response(towards, &response) {
+                directions.push(direction);
+            }
+        }
+        schedules.insert(line.clone(), directions);
+    }
+
+    app.this_StopTimetable.schedules = schedules.clone();
+    if let Some(stop) = &app.this_StopTimetable.stop_point {
+        if let Some(cached) = app.stop_cache.get_mut(&stop.id) {
+            cached.schedules = schedules;
+        }
+    }
+}
+
+async fn load_line_detail(app: &mut App<'_>, index: usize) {
+    let line = match app.lineData.get(index) {
+        Some(l) => l.clone(),
+        None => return,
+    };
+    let client = app.api_client.as_ref().unwrap();
+
+    let mut stations = client.get(format!("https://api.tfl.gov.uk/Line/{}/StopPoints", line.id))
+        .send()
+        .await
+        .unwrap()
+        .json::<Vec<LineStop>>()
+        .await
+        .unwrap();
+    stations.sort_by(|a, b| a.commonName.cmp(&b.commonName));
+
+    let disruptions = client.get(format!("https://api.tfl.gov.uk/Line/{}/Disruption", line.id))
+        .send()
+        .await
+        .unwrap()
+        .json::<Vec<Disruption>>()
+        .await
+        .unwrap();
+
+    refresh_line_feed(app, &line.id).await;
+    app.this_LineDetail = Some(LineDetail { line, stations, disruptions });
+}
+
+// fetch a line's arrivals unless the copy we have is still fresh; a failed request keeps the old one,
+// and counts as a refresh so the line waits its turn like the others before trying again
+async fn refresh_line_feed(app: &mut App<'_>, line: &str) {
+    let due = Duration::from_secs(app.config.board_refresh_secs);
+    if app.line_feeds.get(line).is_some_and(|f| f.refreshed_at.elapsed() < due) {
+        return;
+    }
+    let res = app.api_client.as_ref().unwrap().get(format!("https://api.tfl.gov.uk/Line/{}/Arrivals", line))
+        .send()
+        .await;
+    let arrivals = match res {
+        Ok(res) => res.json::<Vec<Arrival>>().await.ok(),
+        Err(_) => None,
+    };
+    let failed = arrivals.is_none();
+    let arrivals = arrivals
+        .or_else(|| app.line_feeds.remove(line).map(|f| f.arrivals))
+        .unwrap_or_default();
+    app.line_feeds.insert(line.to_string(), LineFeed { arrivals, refreshed_at: Instant::now(), failed });
+}
+
+// keep the feeds on screen fresh: the open line on the Lines tab, the mapped lines on the Timetable tab
+async fn refresh_line_feeds(app: &mut App<'_>) {
+    let lines = match app.tab_index {
+        1 => app.this_StopTimetable.live_maps.keys().cloned().collect::<Vec<_>>(),
+        2 => app.this_LineDetail.iter().map(|d| d.line.id.clone()).collect(),
+        _ => return,
+    };
+    let due = Duration::from_secs(app.config.board_refresh_secs);
+    // one request per tick, like the pinned boards
+    let stale = lines
+        .into_iter()
+        .find(|l| app.line_feeds.get(l).is_none_or(|f| f.refreshed_at.elapsed() >= due));
+    if let Some(line) = stale {
+        refresh_line_feed(app, &line).await;
+        if let Some(live_map) = app.this_StopTimetable.live_maps.get_mut(&line) {
+            if let Some(feed) = app.line_feeds.get(&line) {
+                live_map.place_trains(&feed.arrivals);
+            }
+        }
+    }
+}
+
+// search the offline index when there is one, StopPoint/Search otherwise
+async fn search_stop_points(app: &App<'_>, query: &str) -> Vec<StopPoint> {
+    if !app.station_index.is_empty() {
+        return app.station_index
+            .search(query, 8)
+            .into_iter()
+            .map(StopPoint::from)
+            .collect();
+    }
+
+    // the query is whatever was typed, so it goes in as an encoded path segment
+    let mut url = Url::parse("https://api.tfl.gov.uk/StopPoint/Search").unwrap();
+    url.path_segments_mut().unwrap().push(query);
+    url.query_pairs_mut()
+        .append_pair("modes", &app.config.modes.join(","))
+        .append_pair("includeHubs", "false");
+    let res = app.api_client.as_ref().unwrap().get(url)
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<StopPointResponse>().await {
+            Ok(search) => search.matches.into_iter().flatten().collect(),
+            Err(_) => Vec::new(),
+        },
+        Err(_) => Vec::new(),
+    }
+}
+
+// fetch arrivals for a stop; None if the request fails
+async fn fetch_arrivals(app: &App<'_>, stop_id: &str) -> Option<Vec<Arrival>> {
+    app.api_client.as_ref().unwrap().get(format!("https://api.tfl.gov.uk/StopPoint/{}/Arrivals?mode={}", stop_id, app.config.modes.join(",")))
+        .send()
+        .await
+        .ok()?
+        .json::<Vec<Arrival>>()
+        .await
+        .ok()
+}
+
+// the individual stops (with stop letters) behind a bus stop area
+async fn fetch_bus_stops(app: &App<'_>, stop_id: &str) -> Vec<StopPoint> {
+    let res = app.api_client.as_ref().unwrap().get(format!("https://api.tfl.gov.uk/StopPoint/{}", stop_id))
+        .send()
+        .await;
+    match res {
+        Ok(res) => match res.json::<StopPointDetail>().await {
+            Ok(detail) => detail.bus_stops(),
+            Err(_) => Vec::new(),
+        },
+        Err(_) => Vec::new(),
+    }
+}
+
+async fn fetch_bus_arrivals(app: &App<'_>, bus_stops: &[StopPoint]) -> Vec<Arrival> {
+    let mut arrivals = Vec::new();
+    for stop in bus_stops {
+        arrivals.extend(fetch_arrivals(app, &stop.id).await.unwrap_or_default());
+    }
+    arrivals
+}
+
+// arrivals for a stop we already know; bus stop areas are fetched stop by stop
+async fn refresh_arrivals(app: &App<'_>, timetable: &StopTimetable) -> Option<Vec<Arrival>> {
+    let stop_id = timetable.stop_point.as_ref().map(|s| s.id.clone()).unwrap_or_default();
+    match timetable.bus_stops.is_empty() {
+        true => fetch_arrivals(app, &stop_id).await,
+        false => Some(fetch_bus_arrivals(app, &timetable.bus_stops).await),
+    }
+}
+
+// the followed train's upcoming stops, polled every follow_refresh_secs
+async fn refresh_followed(app: &mut App<'_>) {
+    let (vehicle_id, line_id) = match &app.followed {
+        Some(t) if t.refreshed_at.elapsed() >= Duration::from_secs(app.config.follow_refresh_secs) => (t.vehicle_id.clone(), t.line_id.clone()),
+        _ => return,
+    };
+    let stops = fetch_vehicle_arrivals(app, &vehicle_id, &line_id).await;
+    if let Some(train) = app.followed.as_mut() {
+        train.refreshed_at = Instant::now();
+        // a failed request keeps the last known stops
+        if let Some(stops) = stops {
+            train.stops = stops;
+        }
+    }
+}
+
+// train numbers are only unique within a line, so other lines' trains with the same number are dropped
+async fn fetch_vehicle_arrivals(app: &App<'_>, vehicle_id: &str, line_id: &str) -> Option<Vec<Arrival>> {
+    let mut stops = app.api_client.as_ref().unwrap().get(format!("https://api.tfl.gov.uk/Vehicle/{}/Arrivals", vehicle_id))
+        .send()
+        .await
+        .ok()?
+        .json::<Vec<Arrival>>()
+        .await
+        .ok()?;
+    stops.retain(|a| a.lineId == line_id);
+    stops.sort_by_key(|a| a.timeToStation);
+    // the same stop can be predicted more than once; keep the soonest
+    let mut seen = BTreeSet::new();
+    stops.retain(|a| seen.insert(a.naptanId.clone()));
+    Some(stops)
+}
+
+async fn follow_selected(app: &mut App<'_>) {
+    let arrival = match app.selected_arrival() {
+        Some(a) => a.clone(),
+        None => return,
+    };
+    let stops = fetch_vehicle_arrivals(app, &arrival.vehicleId, &arrival.lineId).await.unwrap_or_default();
+    app.followed = Some(FollowedTrain {
+        vehicle_id: arrival.vehicleId.clone(),
+        line_id: arrival.lineId.clone(),
+        destination: arrival.destination(),
+        stops,
+        refreshed_at: Instant::now(),
+    });
+}
+
+// look up a station and everything drawn for it: lines, platforms and line maps
+async fn build_stop_timetable(app: &App<'_>, stop_point: StopPoint) -> StopTimetable {
+    let mut timetable = StopTimetable::default();
+
+    // use id to fetch arrivals
+    let mut arrivals = fetch_arrivals(app, &stop_point.id).await.unwrap_or_default();
+    if stop_point.is_bus() {
+        timetable.bus_stops = fetch_bus_stops(app, &stop_point.id).await;
+        if arrivals.is_empty() {
+            arrivals = fetch_bus_arrivals(app, &timetable.bus_stops).await;
+        }
+    }
+    timetable.stop_point = Some(stop_point);
+    timetable.set_arrivals(arrivals);
+
+    map_lines(app, &mut timetable).await;
+    timetable.refreshed_at = Some(Instant::now());
+    timetable
+}
+
+// line maps for every line at the station that doesn't have one yet
+async fn map_lines(app: &App<'_>, timetable: &mut StopTimetable) {
+    let unmapped = timetable.unique_lines
+        .iter()
+        .filter(|l| !timetable.live_maps.contains_key(*l))
+        .cloned()
+        .collect::<Vec<_>>();
+    for u_line in &unmapped {
+        // line maps are only drawn for tube lines
+        if !timetable.arrivals.iter().any(|a| a.lineId == *u_line && a.modeName == "tube") {
+            continue;
+        }
+        let res = match app.api_client.as_ref().unwrap().get(format!("https://api.tfl.gov.uk/Line/{}/Route/Sequence/all", u_line)).send().await {
+            Ok(res) => match res.json::<RouteResponse>().await {
+                Ok(r) => r,
+                Err(_) => continue,
+            },
+            Err(_) => continue,
+        };
+
+        // some lines come back with a single route, branched lines with many
+        let live_map = LiveMap::from_routes(&res.orderedLineRoutes);
+        let (lowest, _) = live_map.lanes();
+        let current = timetable.stop_point.as_ref().map(|s| s.id.clone()).unwrap_or_default();
+        let mut rects: Vec<StationNode> = Vec::new();
+        for stop in &live_map.stations {
+            rects.push(
+                StationNode {
+                    naptan_id: stop.naptan_id.clone(),
+                    rect: Rectangle {
+                        x: stop.column - NODE_WIDTH / 2.0,
+                        // in the lower half of the station's lane, leaving the upper half for its label
+                        y: (stop.lane - lowest) as f64 * LANE_HEIGHT + LANE_HEIGHT / 8.0,
+                        width: NODE_WIDTH,
+                        height: LANE_HEIGHT / 4.0,
+                        color: match stop.naptan_id == current {
+                            true => Color::LightGreen,
+                            false => app.palette.line(u_line)
+                        }
+                    },
+                }
+            );
+        }
+        timetable.live_maps.insert(u_line.to_string(), live_map);
+        timetable.station_nodes.insert(u_line.to_string(), rects);
+    }
+}
+
+// StopPoint/{ids} takes a comma separated list; keep the URLs a sensible length
+const STATION_BATCH: usize = 20;
+
+// names and interchanges for every station on the line maps, fetched in batches and cached on the app
+async fn label_stations(app: &mut App<'_>, timetable: &mut StopTimetable) {
+    let missing = timetable.live_maps
+        .values()
+        .flat_map(|m| m.stations.iter())
+        .map(|s| s.naptan_id.clone())
+        .filter(|id| !app.station_info.contains_key(id))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    for batch in missing.chunks(STATION_BATCH) {
+        let res = app.api_client.as_ref().unwrap().get(format!("https://api.tfl.gov.uk/StopPoint/{}", batch.join(",")))
+            .send()
+            .await;
+        let details = match res {
+            Ok(res) => res.json::<OneOrMany<StopPointDetail>>().await.map(|d| d.into_vec()).unwrap_or_default(),
+            Err(_) => Vec::new(),
+        };
+        for detail in details {
+            app.station_info.insert(detail.naptanId.clone(), StationInfo {
+                name: detail.commonName.trim_end_matches(" Underground Station").to_string(),
+                zone: detail.additionalProperties
+                    .iter()
+                    .find(|p| p.key == "Zone")
+                    .map(|p| p.value.clone())
+                    .unwrap_or_default(),
+                lines: detail.lines.iter().map(|l| l.id.clone()).collect(),
+            });
+        }
+    }
+
+    let current = timetable.stop_point.as_ref().map(|s| s.id.clone()).unwrap_or_default();
+    for (line, live_map) in timetable.live_maps.iter_mut() {
+        // interchanges stand out on the diagram
+        for node in timetable.station_nodes.get_mut(line).into_iter().flatten() {
+            let interchange = app.station_info.get(&node.naptan_id).is_some_and(|i| i.lines.iter().any(|l| l != line));
+            if interchange && node.naptan_id != current {
+                node.rect.color = Color::White;
+            }
+        }
+        for station in live_map.stations.iter_mut() {
+            match app.station_info.get(&station.naptan_id) {
+                Some(info) => {
+                    station.name = info.name.clone();
+                    station.interchange = info.lines.iter().any(|l| l != line);
+                }
+                // fall back to the offline index for the name
+                None => {
+                    if let Some(s) = app.station_index.stations.iter().find(|s| s.id == station.naptan_id) {
+                        station.name = s.name.trim_end_matches(" Underground Station").to_string();
+                    }
+                }
+            }
+        }
+    }
+}
+
+// every train on each mapped line, from the shared line feeds
+async fn refresh_live_maps(app: &mut App<'_>, timetable: &mut StopTimetable) {
+    for (line, live_map) in timetable.live_maps.iter_mut() {
+        refresh_line_feed(app, line).await;
+        if let Some(feed) = app.line_feeds.get(line) {
+            live_map.place_trains(&feed.arrivals);
+        }
+    }
+}
+
+// load the timetable for the station named in app.this_station_name, from the cache where possible;
+// boards are cached by stop id, as the lettered bus stops of an area all share its name
+async fn load_station(app: &mut App<'_>, stop_point: Option<StopPoint>) {
+    app.timetable_scroll = 0;
+    app.platform_scroll = 0;
+    app.arrival_selected = None;
+    app.map_pan = 0.0;
+    app.map_cursor = None;
+    app.geo_pan = (0.0, 0.0);
+
+    // get stop ID -> stop_point.id, unless one was picked from the suggestions
+    let stop_point = match stop_point {
+        Some(s) => s,
+        None => search_stop_points(app, &app.this_station_name).await.into_iter().next().unwrap_or_default(),
+    };
+    let id = stop_point.id.clone();
+
+    let cached = match id.is_empty() {
+        true => None,
+        false => app.stop_cache.get(&id).cloned(),
+    };
+    // a failed request keeps the cached board, and isn't logged: its trains would all look to have arrived
+    let (timetable, fetched) = match cached {
+        // retrieve the cache, update only refreshed data
+        Some(mut timetable) => {
+            let arrivals = refresh_arrivals(app, &timetable).await;
+            let fetched = arrivals.is_some();
+            if let Some(arrivals) = arrivals {
+                timetable.set_arrivals(arrivals);
+                map_lines(app, &mut timetable).await;
+                label_stations(app, &mut timetable).await;
+            }
+            refresh_live_maps(app, &mut timetable).await;
+            timetable.refreshed_at = Some(Instant::now());
+            (timetable, fetched)
+        }
+        None => {
+            let mut timetable = build_stop_timetable(app, stop_point).await;
+            label_stations(app, &mut timetable).await;
+            refresh_live_maps(app, &mut timetable).await;
+            (timetable, true)
+        }
+    };
+    if !id.is_empty() {
+        app.stop_cache.insert(id, timetable.clone());
+    }
+    app.this_StopTimetable = timetable;
+    if let (true, Some(stop)) = (fetched, &app.this_StopTimetable.stop_point) {
+        app.accuracy.record(&stop.id, &app.this_StopTimetable.arrivals);
+    }
+}
+
+// refresh pinned boards one at a time as they fall due, fetching any that are not cached yet
+async fn refresh_pinned(app: &mut App<'_>) {
+    let due = Duration::from_secs(app.config.board_refresh_secs);
+    // the open station keeps itself and its cached board fresh
+    let open = app.this_StopTimetable.stop_point.as_ref().map(|s| s.id.clone());
+    let stale = app.config.pinned_stations
+        .iter()
+        .filter(|id| open.as_ref() != Some(*id))
+        .find(|id| match app.stop_cache.get(*id) {
+            Some(t) => t.refreshed_at.is_none_or(|r| r.elapsed() >= due),
+            None => true,
+        })
+        .cloned();
+    let mut id = match stale {
+        Some(id) => id,
+        None => return,
+    };
+
+    let (timetable, fetched) = match app.stop_cache.get(&id).cloned() {
+        Some(mut timetable) => {
+            let arrivals = refresh_arrivals(app, &timetable).await;
+            let fetched = arrivals.is_some();
+            if let Some(arrivals) = arrivals {
+                timetable.set_arrivals(arrivals);
+            }
+            timetable.refreshed_at = Some(Instant::now());
+            (timetable, fetched)
+        }
+        None => {
+            let stop_point = pinned_stop_point(app, &id).await;
+            // pins from before they were kept by id name the station; swap them for its id
+            if !stop_point.id.is_empty() && stop_point.id != id {
+                for pin in app.config.pinned_stations.iter_mut().filter(|p| **p == id) {
+                    *pin = stop_point.id.clone();
+                }
+                let _ = app.config.save();
+                id = stop_point.id.clone();
+            }
+            (build_stop_timetable(app, stop_point).await, true)
+        }
+    };
+
+    // the old board again would only repeat its predictions
+    if let (true, Some(stop)) = (fetched, &timetable.stop_point) {
+        app.accuracy.record(&stop.id, &timetable.arrivals);
+    }
+    app.stop_cache.insert(id, timetable);
+}
+
+// the open station's board refreshes on the same schedule as the pinned ones, so its trains are logged too
+async fn refresh_open_board(app: &mut App<'_>) {
+    let due = Duration::from_secs(app.config.board_refresh_secs);
+    let id = match &app.this_StopTimetable.stop_point {
+        Some(s) if !s.id.is_empty() && app.this_StopTimetable.refreshed_at.is_none_or(|r| r.elapsed() >= due) => s.id.clone(),
+        _ => return,
+    };
+    let arrivals = refresh_arrivals(app, &app.this_StopTimetable).await;
+    // a failed request is retried when the next refresh falls due, not on every tick
+    app.this_StopTimetable.refreshed_at = Some(Instant::now());
+    if let Some(arrivals) = arrivals {
+        let mut timetable = std::mem::take(&mut app.this_StopTimetable);
+        timetable.set_arrivals(arrivals);
+        // lines that have started running since the station was opened need their maps
+        map_lines(app, &mut timetable).await;
+        label_stations(app, &mut timetable).await;
+        app.accuracy.record(&id, &timetable.arrivals);
+        app.stop_cache.insert(id, timetable.clone());
+        app.this_StopTimetable = timetable;
+    }
+}
+
+// the stop behind a pin: its id, looked up in the offline index, or a station name from an older config
+async fn pinned_stop_point(app: &App<'_>, pin: &str) -> StopPoint {
+    if let Some(station) = app.station_index.get(pin) {
+        return StopPoint::from(station);
+    }
+    match search_stop_points(app, pin).await.into_iter().next() {
+        Some(stop_point) => stop_point,
+        None => StopPoint { id: pin.to_string(), name: pin.to_string(), ..StopPoint::default() },
+    }
+}
+
 #[tokio::main]
 pub async fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
     // create reqwest client
     app.api_client = Some(Client::new());
+    app.palette = Palette::new(app.config.colors);
 
     // load data once here before loop
     let result = app.api_client.as_ref().unwrap().get("https://api.tfl.gov.uk/line/mode/tube/status").send().await.unwrap().json::<Vec<Line>>().await.unwrap();
@@ -195,11 +1483,17 @@ pub async fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io
     app.lineData = result;
 
     app.line_cache.insert(String::from("lineNames"), app.lineNames.clone());
+    load_severities(&mut app).await;
 
     // begin loop
     loop {
         terminal.draw(|f| ui(f, &mut app))?;
 
+        on_tick(&mut app).await;
+        if !event::poll(Duration::from_millis(100))? {
+            continue;
+        }
+
         if let Event::Key(key) = event::read()? {
             match app.input_mode {
                 InputMode::Normal => match key.code {
@@ -213,6 +1507,41 @@ pub async fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io
                         app.focus = Some(Focus::InputBlock);
                     }
 
+                    // move along the line diagram; h/l only browse lines outside it
+                    KeyCode::Char('m') if app.tab_index == 1 => app.focus_map(),
+                    KeyCode::Char('h') if matches!(app.focus, Some(Focus::Map)) => app.move_map_cursor(-1),
+                    KeyCode::Char('l') if matches!(app.focus, Some(Focus::Map)) => app.move_map_cursor(1),
+
+                    // browse lines
+                    KeyCode::Char('l') => {
+                        app.tab_index = 2;
+                        app.focus = Some(Focus::LinesBlock);
+                    }
+
+                    // open the selected line
+                    KeyCode::Enter => match app.focus {
+                        Some(Focus::LinesBlock) => {
+                            let selected = app.line_selected.unwrap_or(0);
+                            load_line_detail(&mut app, selected).await;
+                        }
+                        // follow the selected train
+                        None if app.tab_index == 1 => follow_selected(&mut app).await,
+                        // open the station under the map cursor
+                        Some(Focus::Map) => {
+                            if let Some(station) = app.cursor_station().cloned() {
+                                let name = match station.name.is_empty() {
+                                    true => station.naptan_id.clone(),
+                                    false => station.name.clone(),
+                                };
+                                app.this_station_name = name.clone();
+                                app.focus = None;
+                                let stop = StopPoint { id: station.naptan_id, name, modes: vec![String::from("tube")], ..StopPoint::default() };
+                                load_station(&mut app, Some(stop)).await;
+                            }
+                        }
+                        _ => {}
+                    },
+
                     // quit app
                     KeyCode::Char('q') => {
                         return Ok(());
@@ -221,9 +1550,90 @@ pub async fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io
                     // refresh data
                     KeyCode::Char('r') => {
                         // refresh all data here manually
-                        let result = app.api_client.as_ref().unwrap().get("https://api.tfl.gov.uk/line/mode/tube/status").send().await.unwrap().json::<Vec<Line>>().await.unwrap();
-                        app.lineNames = app.line_cache["lineNames"].clone();
-                        app.lineData = result;
+                        refresh_line_status(&mut app).await;
+                        // try the network map again if it failed to load
+                        if app.network.is_empty() {
+                            app.network.attempted = false;
+                        }
+                    }
+
+                    // sort and filter the dashboard
+                    KeyCode::Char('s') if app.tab_index == 0 => app.toggle_sort(DashboardSort::Severity),
+                    KeyCode::Char('n') if app.tab_index == 0 => app.toggle_sort(DashboardSort::Name),
+                    KeyCode::Char('f') if app.tab_index == 0 => app.toggle_sort(DashboardSort::Favourites),
+                    KeyCode::Char('d') if app.tab_index == 0 => app.disrupted_only = !app.disrupted_only,
+
+                    // switch between countdown and clock times
+                    KeyCode::Char('t') if app.tab_index == 1 => {
+                        app.config.time_format = app.config.time_format.next();
+                        let _ = app.config.save();
+                    }
+
+                    // switch between live arrivals and the scheduled timetable
+                    KeyCode::Char('s') if app.tab_index == 1 => {
+                        app.timetable_view = match app.timetable_view {
+                            TimetableView::Live => TimetableView::Scheduled,
+                            TimetableView::Scheduled => TimetableView::Live,
+                        };
+                        if app.timetable_view == TimetableView::Scheduled && app.this_StopTimetable.schedules.is_empty() {
+                            load_schedules(&mut app).await;
+                        }
+                    }
+
+                    // pin / unpin the current station on the Watch tab
+                    KeyCode::Char('p') if app.tab_index == 1 && app.this_StopTimetable.stop_point.as_ref().is_some_and(|s| !s.id.is_empty()) => {
+                        let id = app.this_StopTimetable.stop_point.as_ref().unwrap().id.clone();
+                        app.config.toggle_pinned(&id);
+                        let _ = app.config.save();
+                    }
+
+                    // select a train on the board
+                    KeyCode::Down if app.tab_index == 1 => app.move_arrival_selection(1),
+                    KeyCode::Up if app.tab_index == 1 => app.move_arrival_selection(-1),
+
+                    // zoom and pan the line diagrams
+                    KeyCode::Char('+') | KeyCode::Char('=') if app.tab_index == 1 => app.map_zoom = (app.map_zoom * 1.5).min(MAX_MAP_ZOOM),
+                    KeyCode::Char('-') if app.tab_index == 1 => app.map_zoom = (app.map_zoom / 1.5).max(1.0),
+                    KeyCode::Char(',') if app.tab_index == 1 => app.pan_map(-MAP_PAN_STEP),
+                    KeyCode::Char('.') if app.tab_index == 1 => app.pan_map(MAP_PAN_STEP),
+                    KeyCode::Char('0') if app.tab_index == 1 => {
+                        app.map_zoom = 1.0;
+                        app.map_pan = 0.0;
+                    }
+
+                    // zoom and pan the geographic map, by a fraction of what is on screen
+                    KeyCode::Char('+') | KeyCode::Char('=') if app.tab_index == 6 => app.geo_zoom = (app.geo_zoom * 1.5).min(MAX_GEO_ZOOM),
+                    KeyCode::Char('-') if app.tab_index == 6 => app.geo_zoom = (app.geo_zoom / 1.5).max(1.0),
+                    KeyCode::Char(',') if app.tab_index == 6 => app.pan_geo(-GEO_PAN_STEP, 0.0),
+                    KeyCode::Char('.') if app.tab_index == 6 => app.pan_geo(GEO_PAN_STEP, 0.0),
+                    KeyCode::Char('0') if app.tab_index == 6 => {
+                        app.geo_zoom = 1.0;
+                        app.geo_pan = (0.0, 0.0);
+                    }
+
+                    // stop following
+                    KeyCode::Char('u') if app.tab_index == 1 => app.followed = None,
+
+                    // group the arrival boards
+                    KeyCode::Char('g') if app.tab_index == 1 => app.board_grouping = app.board_grouping.next(),
+
+                    // export the prediction log
+                    KeyCode::Char('e') if app.tab_index == 4 => {
+                        if let Err(e) = app.accuracy.export() {
+                            app.accuracy.last_export = Some(format!("export failed: {}", e));
+                        }
+                    }
+
+                    // dismiss the alert banner, showing the next one if any
+                    KeyCode::Char('x') => app.dismiss_alert(),
+
+                    // watch / unwatch the selected line
+                    KeyCode::Char('w') if matches!(app.focus, Some(Focus::LinesBlock)) => {
+                        if let Some(line) = app.lineData.get(app.line_selected.unwrap_or(0)) {
+                            let id = line.id.clone();
+                            app.config.toggle_watched(&id);
+                            let _ = app.config.save();
+                        }
                     }
 
                     // leave focus
@@ -231,187 +1641,103 @@ pub async fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io
                         app.focus = None;
                     }
                     KeyCode::Char('j') => match app.focus {
-                        Some(Focus::LinesBlock) => {
+                        Some(Focus::LinesBlock)
                             if app.lines_tree_size
                                 > usize::checked_add(
                                     app.line_selected.unwrap(),
                                     usize::try_from(1).unwrap(),
-                                )
-                            {
-                                app.line_selected = usize::checked_add(
-                                    app.line_selected.unwrap(),
-                                    usize::try_from(1).unwrap(),
-                                );
+                                ) =>
+                        {
+                            app.line_selected = usize::checked_add(
+                                app.line_selected.unwrap(),
+                                usize::try_from(1).unwrap(),
+                            );
+                        }
+                        None if app.tab_index == 1 && app.timetable_scroll + 1 < app.this_StopTimetable.unique_lines.len() => {
+                            app.timetable_scroll += 1;
+                        }
+                        Some(Focus::Map) => {
+                            if app.timetable_scroll + 1 < app.this_StopTimetable.unique_lines.len() {
+                                app.timetable_scroll += 1;
                             }
+                            app.focus_map();
                         }
+                        None if app.tab_index == 6 => app.pan_geo(0.0, -GEO_PAN_STEP),
                         _ => {}
                     },
                     KeyCode::Char('k') => match app.focus {
-                        Some(Focus::LinesBlock) => {
-                            if app.line_selected != Some(0) {
-                                app.line_selected = usize::checked_sub(
-                                    app.line_selected.unwrap(),
-                                    usize::try_from(1).unwrap(),
-                                );
-                            }
+                        Some(Focus::LinesBlock) if app.line_selected != Some(0) => {
+                            app.line_selected = usize::checked_sub(
+                                app.line_selected.unwrap(),
+                                usize::try_from(1).unwrap(),
+                            );
+                        }
+                        None if app.tab_index == 1 => {
+                            app.timetable_scroll = app.timetable_scroll.saturating_sub(1);
+                        }
+                        Some(Focus::Map) => {
+                            app.timetable_scroll = app.timetable_scroll.saturating_sub(1);
+                            app.focus_map();
                         }
+                        None if app.tab_index == 6 => app.pan_geo(0.0, GEO_PAN_STEP),
                         _ => {}
                     }
+
+                    // page through platforms
+                    KeyCode::Char(']') if app.tab_index == 1 => {
+                        let most = app.this_StopTimetable.unique_lines
+                            .iter()
+                            .map(|l| app.this_StopTimetable.board_groups(l, app.board_grouping).len())
+                            .max()
+                            .unwrap_or(0);
+                        if app.platform_scroll + 1 < most {
+                            app.platform_scroll += 1;
+                        }
+                    }
+                    KeyCode::Char('[') if app.tab_index == 1 => app.platform_scroll = app.platform_scroll.saturating_sub(1),
                     _ => {}
                 }
                 InputMode::Insert => match key.code {
                     KeyCode::Enter => {
-                        app.this_station_name = app.input.drain(..).collect();
-                        let _ = app.this_StopTimetable.unique_lines.drain();
-
-                        if app.stop_cache.contains_key(&app.this_station_name) {
-                            // retrieve the cache
-                            // update only refreshed data
-
-                            app.this_StopTimetable.stop_point = app.stop_cache[&app.this_station_name].stop_point.clone();
-                            app.this_StopTimetable.unique_lines = app.stop_cache[&app.this_station_name].unique_lines.clone();
-                            app.this_StopTimetable.unique_platforms = app.stop_cache[&app.this_station_name].unique_platforms.clone();
-                            app.this_StopTimetable.live_maps = app.stop_cache[&app.this_station_name].live_maps.clone();
-                            app.this_StopTimetable.station_nodes = app.stop_cache[&app.this_station_name].station_nodes.clone();
-
-                            // use id to fetch arrivals
-                            app.this_StopTimetable.arrivals = app.api_client.as_ref().unwrap().get(format!("https://api.tfl.gov.uk/StopPoint/{}/Arrivals?mode=tube", app.this_StopTimetable.stop_point.as_ref().unwrap().id))
-                                .send()
-                                .await
-                                .unwrap()
-                                .json::<Vec<Arrival>>()
-                                .await
-                                .unwrap();
-                        }
-
-                        else {
-                            // get stop ID -> stop_point.id
-                            let stop_id_search =  app.api_client.as_ref().unwrap().get(format!("https://api.tfl.gov.uk/StopPoint/Search/{}?modes=tube&includeHubs=false", app.this_station_name))
-                                .send()
-                                .await
-                                .unwrap()
-                                .json::<StopPointResponse>()
-                                .await
-                                .unwrap();
-                            app.this_StopTimetable.stop_point = match &stop_id_search.matches.len() {
-                                0 => Some(StopPoint::default()),
-                                _ => stop_id_search.matches[0].clone()
-                            };
-
-                            // use id to fetch arrivals
-                            app.this_StopTimetable.arrivals =  app.api_client.as_ref().unwrap().get(format!("https://api.tfl.gov.uk/StopPoint/{}/Arrivals?mode=tube", app.this_StopTimetable.stop_point.as_ref().unwrap().id))
-                                .send()
-                                .await
-                                .unwrap()
-                                .json::<Vec<Arrival>>()
-                                .await
-                                .unwrap();
-
-                            for arrival in &app.this_StopTimetable.arrivals {
-                                app.this_StopTimetable.unique_lines.insert(arrival.lineId.clone());
-                            }
-
-                            // over all lines in this station
-                            for u_line in &app.this_StopTimetable.unique_lines {
-                                let platforms_for_this_line = app.this_StopTimetable.arrivals
-                                    .iter()
-                                    .enumerate()
-                                    .filter(|&(_,i)| i.lineId == u_line.clone())
-                                    .map(|(_,e)| e.platformName.clone())
-                                    .collect::<Vec<String>>();
-
-                                // sort platforms by line
-                                let mut map: BTreeMap<String, _> = BTreeMap::new();
-                                for platform in platforms_for_this_line {
-                                    map.entry(platform.clone()).or_insert(platform);
-                                }
-                                let mut platforms: Vec<String> = Vec::new();
-                                for (platform, _) in &map {
-                                    platforms.push(platform.clone());
-                                }
-                                // { key: line(String), value: platform(String) }
-                                app.this_StopTimetable.unique_platforms.insert(u_line.to_string(), platforms);
-
-
-                                //
-                                let res =  app.api_client.as_ref().unwrap().get(format!("https://api.tfl.gov.uk/Line/{}/Route/Sequence/all", u_line))
-                                    .send()
-                                    .await
-                                    .unwrap()
-                                    .json::<RouteResponse>()
-                                    .await
-                                    .unwrap();
-                                
-                                app.this_StopTimetable.live_maps.insert(u_line.to_string(), LiveMap { 
-                                    stops_0: res.orderedLineRoutes[0].naptanIds
-                                            .iter()
-                                            .map(|s| Station::new(s.to_string()))
-                                            .collect::<Vec<Station>>(),
-                                    stops_1: res.orderedLineRoutes[1].naptanIds
-                                            .iter()
-                                            .map(|s| Station::new(s.to_string()))
-                                            .collect::<Vec<Station>>(),
-                                    trains_currently_at: Vec::new()
-                                    }
-                                );
-
-
-
-                                let mut x_0 = 12.5;
-                                let y = 50.0;
-                                let mut x_1 = 12.5;
-                                let mut rects_0: Vec<StationNode> = Vec::new();
-                                let mut rects_1: Vec<StationNode> = Vec::new();
-                                for stop in &app.this_StopTimetable.live_maps[u_line].stops_0 {
-                                    rects_0.push(
-                                        StationNode {
-                                            naptan_id: stop.naptan_id.clone(),
-                                            rect: Rectangle {
-                                                x:x_0,
-                                                y:y,
-                                                width:2.0,
-                                                height:10.0,
-                                                color: match &stop.naptan_id == &app.this_StopTimetable.stop_point.clone().unwrap().id {
-                                                    true => Color::LightGreen,
-                                                    false => Color::LightYellow
-                                                }
-                                            },
-                                        }
-                                    );
-                                    x_0 += 3.5;
-                                }
-                                for stop in &app.this_StopTimetable.live_maps[u_line].stops_1 {
-                                    rects_1.push(
-                                        StationNode { 
-                                            naptan_id: stop.naptan_id.clone(),
-                                            rect: Rectangle {
-                                                x:x_1,
-                                                y:y,
-                                                width:2.0,
-                                                height:10.0,
-                                                color: match &stop.naptan_id == &app.this_StopTimetable.stop_point.clone().unwrap().id {
-                                                    true => Color::LightGreen,
-                                                    false => Color::LightYellow
-                                                }
-                                            },
-                                        }
-                                    );
-                                    x_1 += 3.5;
-                                }
-                                app.this_StopTimetable.station_nodes.insert(u_line.to_string(), vec!(rects_0, rects_1));
-                            }
-                            app.stop_cache.insert(format!("{}", app.this_station_name), app.this_StopTimetable.clone());
-                        }
+                        let picked = app.suggestion_selected.and_then(|i| app.suggestions.get(i).cloned());
+                        let typed: String = app.input.drain(..).collect();
+                        app.this_station_name = match &picked {
+                            Some(s) => s.name.clone(),
+                            None => typed,
+                        };
+                        app.suggestions.clear();
+                        app.suggestion_selected = None;
+                        app.input_changed_at = None;
+                        load_station(&mut app, picked).await;
                     }
                     KeyCode::Char(c) => {
                         app.input.push(c);
+                        app.input_changed_at = Some(Instant::now());
                     }
                     KeyCode::Backspace => {
                         app.input.pop();
+                        app.input_changed_at = Some(Instant::now());
+                    }
+
+                    // move through the suggestions
+                    KeyCode::Down if !app.suggestions.is_empty() => {
+                        app.suggestion_selected = Some(match app.suggestion_selected {
+                            Some(i) => (i + 1).min(app.suggestions.len() - 1),
+                            None => 0,
+                        });
+                    }
+                    KeyCode::Up => {
+                        app.suggestion_selected = match app.suggestion_selected {
+                            Some(0) | None => None,
+                            Some(i) => Some(i - 1),
+                        };
                     }
                     KeyCode::Esc => {
                         app.input_mode = InputMode::Normal;
                         app.focus = None;
+                        app.suggestions.clear();
+                        app.suggestion_selected = None;
+                        app.input_changed_at = None;
                     }
                     _ => {}
                 }
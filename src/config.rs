@@ -0,0 +1,107 @@
+use std::{fs, io, path::PathBuf};
+use serde_derive::{Serialize, Deserialize};
+
+use crate::palette::ColorMode;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeFormat {
+    Countdown,
+    Clock,
+    Both,
+}
+impl TimeFormat {
+    pub fn next(&self) -> TimeFormat {
+        match self {
+            TimeFormat::Countdown => TimeFormat::Clock,
+            TimeFormat::Clock => TimeFormat::Both,
+            TimeFormat::Both => TimeFormat::Countdown,
+        }
+    }
+}
+
+// user settings, read from <config dir>/ttfl/config.json
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Config {
+    pub watched_lines: Vec<String>,
+    pub bell: bool,
+    pub alert_command: Option<String>,
+    pub refresh_secs: u64,
+    pub time_format: TimeFormat,
+    pub last_train_warning_mins: i32,
+    pub pinned_stations: Vec<String>,
+    pub board_refresh_secs: u64,
+    pub modes: Vec<String>,
+    pub follow_refresh_secs: u64,
+    pub colors: ColorMode,
+    // why the file on disk couldn't be read; while set, save() leaves the file alone
+    #[serde(skip)]
+    pub unreadable: Option<String>,
+}
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            watched_lines: Vec::new(),
+            bell: false,
+            alert_command: None,
+            refresh_secs: 60,
+            time_format: TimeFormat::Countdown,
+            last_train_warning_mins: 30,
+            pinned_stations: Vec::new(),
+            board_refresh_secs: 30,
+            modes: vec![String::from("tube")],
+            follow_refresh_secs: 15,
+            colors: ColorMode::Auto,
+            unreadable: None,
+        }
+    }
+}
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("ttfl").join("config.json"))
+    }
+    // a missing file falls back to the defaults; a broken one does too, but is kept for the user to fix
+    pub fn load() -> Config {
+        let path = match Config::path() {
+            Some(p) => p,
+            None => return Config::default(),
+        };
+        let read = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| serde_json::from_str::<Config>(&s).map_err(|e| e.to_string()));
+        match read {
+            Ok(config) => config,
+            Err(_) if !path.exists() => Config::default(),
+            Err(e) => Config { unreadable: Some(format!("{}: {}", path.display(), e)), ..Config::default() },
+        }
+    }
+    pub fn save(&self) -> io::Result<()> {
+        if self.unreadable.is_some() {
+            return Ok(());
+        }
+        let path = match Config::path() {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+    pub fn is_watched(&self, line_id: &str) -> bool {
+        self.watched_lines.iter().any(|l| l == line_id)
+    }
+    pub fn toggle_pinned(&mut self, station: &str) {
+        match self.pinned_stations.iter().position(|s| s == station) {
+            Some(i) => { self.pinned_stations.remove(i); }
+            None => self.pinned_stations.push(station.to_string()),
+        }
+    }
+    pub fn toggle_watched(&mut self, line_id: &str) {
+        match self.watched_lines.iter().position(|l| l == line_id) {
+            Some(i) => { self.watched_lines.remove(i); }
+            None => self.watched_lines.push(line_id.to_string()),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::{BTreeSet, HashMap};
+
+// canvas height given to each lane of a line diagram: a row for labels above a row for the track
+pub const LANE_HEIGHT: f64 = 20.0;
+// canvas width of a station node; one column is one unit wide
+pub const NODE_WIDTH: f64 = 0.4;
+// below this many cells per station the diagram stops shrinking and scrolls instead
+pub const MIN_CELLS_PER_STATION: f64 = 2.0;
+
+// the columns shown of a diagram `columns` stations long in `width` cells: all of it when it fits,
+// otherwise a window around `centre`, narrowed by `zoom` and moved by `pan` columns, kept on the diagram
+pub fn window(columns: f64, width: u16, zoom: f64, centre: f64, pan: f64) -> (f64, f64) {
+    let (first, last) = (-0.5, columns - 0.5);
+    let shown = (columns.min(width as f64 / MIN_CELLS_PER_STATION) / zoom).max(1.0);
+    if shown >= columns {
+        return (first, last);
+    }
+    let start = (centre + pan - shown / 2.0).max(first).min(last - shown);
+    (start, start + shown)
+}
+
+// the pans that move the window: beyond these it is held at an end of the diagram
+pub fn pan_limits(columns: f64, width: u16, zoom: f64, centre: f64) -> (f64, f64) {
+    let (first, last) = (-0.5, columns - 0.5);
+    let shown = (columns.min(width as f64 / MIN_CELLS_PER_STATION) / zoom).max(1.0);
+    if shown >= columns {
+        return (0.0, 0.0);
+    }
+    (first - centre + shown / 2.0, last - shown - centre + shown / 2.0)
+}
+
+// a station placed on the diagram; columns can be fractional where a short branch is squeezed between stations
+#[derive(Debug, Clone)]
+pub struct PlacedStop {
+    pub naptan_id: String,
+    pub column: f64,
+    pub lane: i32,
+}
+
+// every route sequence of a line merged into one branching diagram
+#[derive(Debug, Clone, Default)]
+pub struct DiagramLayout {
+    pub stops: Vec<PlacedStop>,
+    // each route as indexes into stops, all running the same way along the trunk
+    pub routes: Vec<Vec<usize>>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+// the longest route becomes the trunk on lane 0; stations the other routes add are branches on lanes
+// alternating above and below it, starting where they leave the placed stations and ending where they rejoin
+pub fn layout(routes: &[Vec<String>]) -> DiagramLayout {
+    let mut routes = routes.iter().filter(|r| !r.is_empty()).cloned().collect::<Vec<_>>();
+    routes.sort_by_key(|r| std::cmp::Reverse(r.len()));
+
+    let mut diagram = DiagramLayout::default();
+    let mut placed: HashMap<String, usize> = HashMap::new();
+    let mut branches = 0;
+
+    for mut route in routes {
+        // run the same way as the stations already placed; routes back the other way are the same track
+        let columns = route
+            .iter()
+            .filter_map(|id| placed.get(id).map(|&i| diagram.stops[i].column))
+            .collect::<Vec<_>>();
+        if let (Some(first), Some(last)) = (columns.first(), columns.last()) {
+            if first > last {
+                route.reverse();
+            }
+        }
+
+        let mut i = 0;
+        while i < route.len() {
+            if placed.contains_key(&route[i]) {
+                i += 1;
+                continue;
+            }
+            // a run of new stations, between the placed ones either side of it
+            let start = i;
+            while i < route.len() && !placed.contains_key(&route[i]) {
+                i += 1;
+            }
+            let count = i - start;
+            let before = start.checked_sub(1).map(|k| diagram.stops[placed[&route[k]]].column);
+            let after = route.get(i).map(|id| diagram.stops[placed[id]].column);
+            let columns = match (before, after) {
+                (Some(a), Some(b)) => (1..=count).map(|k| a + (b - a) * k as f64 / (count + 1) as f64).collect::<Vec<_>>(),
+                (Some(a), None) => (1..=count).map(|k| a + k as f64).collect(),
+                (None, Some(b)) => (1..=count).rev().map(|k| b - k as f64).collect(),
+                (None, None) => (0..count).map(|k| k as f64).collect(),
+            };
+            let lane = match diagram.stops.is_empty() {
+                true => 0,
+                false => {
+                    branches += 1;
+                    match branches % 2 {
+                        1 => (branches + 1) / 2,
+                        _ => -(branches / 2),
+                    }
+                }
+            };
+            for (id, column) in route[start..i].iter().zip(columns) {
+                placed.insert(id.clone(), diagram.stops.len());
+                diagram.stops.push(PlacedStop { naptan_id: id.clone(), column, lane });
+            }
+        }
+
+        let indexes = route.iter().map(|id| placed[id]).collect::<Vec<_>>();
+        if !diagram.routes.contains(&indexes) {
+            diagram.routes.push(indexes);
+        }
+    }
+
+    // branches off the start of the trunk can push columns below zero
+    let least = diagram.stops.iter().map(|s| s.column).fold(0.0, f64::min);
+    for stop in &mut diagram.stops {
+        stop.column -= least;
+    }
+
+    diagram.edges = diagram.routes
+        .iter()
+        .flat_map(|r| r.windows(2).map(|w| (w[0].min(w[1]), w[0].max(w[1]))))
+        .filter(|(a, b)| a != b)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    diagram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(ids: &str) -> Vec<String> {
+        ids.split_whitespace().map(String::from).collect()
+    }
+
+    fn stop<'a>(diagram: &'a DiagramLayout, id: &str) -> &'a PlacedStop {
+        diagram.stops.iter().find(|s| s.naptan_id == id).unwrap()
+    }
+
+    #[test]
+    fn a_single_route_is_a_straight_trunk() {
+        let diagram = layout(&[route("A B C D")]);
+        assert_eq!(diagram.stops.len(), 4);
+        assert!(diagram.stops.iter().all(|s| s.lane == 0));
+        assert_eq!(diagram.stops.iter().map(|s| s.column).collect::<Vec<_>>(), vec![0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(diagram.edges, vec![(0, 1), (1, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn the_return_route_is_the_same_track() {
+        let diagram = layout(&[route("A B C D"), route("D C B A")]);
+        assert_eq!(diagram.stops.len(), 4);
+        assert_eq!(diagram.routes.len(), 1);
+        assert_eq!(diagram.edges.len(), 3);
+    }
+
+    // routes are placed longest first, so each test's trunk is its longest route
+    #[test]
+    fn branches_alternate_above_and_below_the_trunk() {
+        let diagram = layout(&[route("A B C D E"), route("A B X Y"), route("P Q C D")]);
+        assert_eq!(stop(&diagram, "X").lane, 1);
+        assert_eq!(stop(&diagram, "P").lane, -1);
+        // a branch off the end carries on from where it leaves the trunk
+        assert_eq!(stop(&diagram, "X").column, stop(&diagram, "B").column + 1.0);
+        assert_eq!(stop(&diagram, "Y").column, stop(&diagram, "B").column + 2.0);
+        // one off the start pushes everything right so no column is negative
+        assert!(diagram.stops.iter().all(|s| s.column >= 0.0));
+        assert_eq!(stop(&diagram, "P").column + 1.0, stop(&diagram, "Q").column);
+    }
+
+    #[test]
+    fn a_loop_is_squeezed_between_the_stations_it_rejoins() {
+        let diagram = layout(&[route("A B C D E F G"), route("A B M N D E")]);
+        let (b, d) = (stop(&diagram, "B").column, stop(&diagram, "D").column);
+        let (m, n) = (stop(&diagram, "M").column, stop(&diagram, "N").column);
+        assert!(b < m && m < n && n < d);
+        assert_eq!(stop(&diagram, "M").lane, 1);
+    }
+
+    #[test]
+    fn empty_routes_are_ignored() {
+        let diagram = layout(&[Vec::new(), route("A B")]);
+        assert_eq!(diagram.stops.len(), 2);
+        assert_eq!(diagram.routes.len(), 1);
+    }
+
+    #[test]
+    fn window_shows_everything_that_fits() {
+        assert_eq!(window(10.0, 100, 1.0, 5.0, 0.0), (-0.5, 9.5));
+    }
+
+    #[test]
+    fn panning_to_the_limits_reaches_the_ends() {
+        let (least, most) = pan_limits(40.0, 20, 1.0, 20.0);
+        assert_eq!(window(40.0, 20, 1.0, 20.0, least), (-0.5, 9.5));
+        assert_eq!(window(40.0, 20, 1.0, 20.0, most), (29.5, 39.5));
+        assert_eq!(pan_limits(10.0, 100, 1.0, 5.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn window_stays_on_the_diagram() {
+        // 40 columns in 20 cells: 10 stations shown at a time
+        let (start, end) = window(40.0, 20, 1.0, 0.0, 0.0);
+        assert_eq!((start, end), (-0.5, 9.5));
+        let (start, end) = window(40.0, 20, 1.0, 39.0, 100.0);
+        assert_eq!((start, end), (29.5, 39.5));
+    }
+}
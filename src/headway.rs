@@ -0,0 +1,225 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::app::{Arrival, BoardGrouping, LineFeed};
+
+// a gap this many times the usual headway, and at least MIN_GAP_SECS, is a gap in service
+const GAP_FACTOR: f64 = 2.0;
+const MIN_GAP_SECS: i32 = 8 * 60;
+
+// an unusually long wait between two consecutive trains, named by where each of them is heading next
+#[derive(Debug, Clone)]
+pub struct ServiceGap {
+    pub secs: i32,
+    pub behind: String,
+    pub ahead: String,
+}
+impl ServiceGap {
+    pub fn label(&self) -> String {
+        match self.behind == self.ahead {
+            true => format!("{}-minute gap at {}", self.secs / 60, self.ahead),
+            false => format!("{}-minute gap between {} and {}", self.secs / 60, self.behind, self.ahead),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DirectionHeadways {
+    pub direction: String,
+    pub trains: usize,
+    pub median_secs: Option<i32>,
+    pub longest_secs: Option<i32>,
+    pub gaps: Vec<ServiceGap>,
+}
+
+// headways are the waits between consecutive trains predicted at the same station,
+// taken over every station in a direction so a gap shows wherever it is on the line
+pub fn analyse(feed: &LineFeed) -> Vec<DirectionHeadways> {
+    let mut directions: BTreeMap<String, Vec<&Arrival>> = BTreeMap::new();
+    for arrival in &feed.arrivals {
+        directions.entry(arrival.board_group(BoardGrouping::Direction)).or_default().push(arrival);
+    }
+    let next_stops = feed
+        .trains()
+        .into_iter()
+        .map(|a| (a.train_key(), short_name(&a.stationName)))
+        .collect::<BTreeMap<_, _>>();
+
+    directions
+        .into_iter()
+        .map(|(direction, arrivals)| {
+            // without a direction, each platform stands in for one, so trains going opposite ways aren't paired
+            let mut stations: BTreeMap<(&str, &str), Vec<&Arrival>> = BTreeMap::new();
+            for arrival in &arrivals {
+                let platform = match arrival.direction.is_empty() {
+                    true => arrival.platformName.as_str(),
+                    false => "",
+                };
+                stations.entry((arrival.naptanId.as_str(), platform)).or_default().push(arrival);
+            }
+
+            // (seconds, train ahead, train behind) for each consecutive pair at each station
+            let mut headways = Vec::new();
+            for station in stations.values_mut() {
+                station.sort_by_key(|a| a.timeToStation);
+                // a train on a loop can be predicted here again after going round; only its next visit counts
+                let mut seen = BTreeSet::new();
+                station.retain(|a| seen.insert(a.train_key()));
+                for pair in station.windows(2) {
+                    headways.push((pair[1].timeToStation - pair[0].timeToStation, pair[0].train_key(), pair[1].train_key()));
+                }
+            }
+
+            let mut secs = headways.iter().map(|(s, _, _)| *s).collect::<Vec<_>>();
+            secs.sort_unstable();
+            let median_secs = match secs.len() {
+                0 => None,
+                n if n % 2 == 0 => Some((secs[n / 2 - 1] + secs[n / 2]) / 2),
+                n => Some(secs[n / 2]),
+            };
+            let longest_secs = secs.last().copied();
+
+            // the same two trains are consecutive at many stations; keep their widest gap
+            let mut gaps: BTreeMap<(String, String), i32> = BTreeMap::new();
+            if let Some(median) = median_secs {
+                let threshold = (median as f64 * GAP_FACTOR).max(MIN_GAP_SECS as f64) as i32;
+                for (s, ahead, behind) in headways.into_iter().filter(|(s, _, _)| *s >= threshold) {
+                    let gap = gaps.entry((ahead, behind)).or_insert(s);
+                    *gap = (*gap).max(s);
+                }
+            }
+            let mut gaps = gaps
+                .into_iter()
+                .map(|((ahead, behind), secs)| ServiceGap {
+                    secs,
+                    behind: next_stops.get(&behind).cloned().unwrap_or_default(),
+                    ahead: next_stops.get(&ahead).cloned().unwrap_or_default(),
+                })
+                .collect::<Vec<_>>();
+            gaps.sort_by_key(|g| std::cmp::Reverse(g.secs));
+
+            DirectionHeadways {
+                direction,
+                trains: arrivals.iter().map(|a| a.train_key()).collect::<BTreeSet<_>>().len(),
+                median_secs,
+                longest_secs,
+                gaps,
+            }
+        })
+        .collect()
+}
+
+fn short_name(name: &str) -> String {
+    name.trim_end_matches(" Underground Station").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn arrival(vehicle_id: &str, station: &str, platform: &str, direction: &str, time_to_station: i32) -> Arrival {
+        Arrival {
+            stationName: format!("{} Underground Station", station),
+            naptanId: station.to_string(),
+            lineId: String::from("circle"),
+            platformName: platform.to_string(),
+            direction: direction.to_string(),
+            destinationName: String::new(),
+            vehicleId: vehicle_id.to_string(),
+            modeName: String::from("tube"),
+            timeToStation: time_to_station,
+            currentLocation: String::new(),
+            expectedArrival: String::new(),
+            towards: String::new(),
+        }
+    }
+
+    fn feed(arrivals: Vec<Arrival>) -> LineFeed {
+        LineFeed { arrivals, refreshed_at: Instant::now(), failed: false }
+    }
+
+    #[test]
+    fn no_arrivals_means_no_headways() {
+        assert!(analyse(&feed(vec![])).is_empty());
+    }
+
+    #[test]
+    fn one_pair_is_its_own_median_and_never_a_gap() {
+        let headways = analyse(&feed(vec![
+            arrival("1", "Moorgate", "Platform 1", "outbound", 60),
+            arrival("2", "Moorgate", "Platform 1", "outbound", 1500),
+        ]));
+
+        assert_eq!(headways.len(), 1);
+        assert_eq!(headways[0].trains, 2);
+        assert_eq!(headways[0].median_secs, Some(1440));
+        assert_eq!(headways[0].longest_secs, Some(1440));
+        assert!(headways[0].gaps.is_empty());
+    }
+
+    #[test]
+    fn an_even_number_of_headways_takes_the_middle_two() {
+        let headways = analyse(&feed(vec![
+            arrival("1", "Moorgate", "Platform 1", "outbound", 0),
+            arrival("2", "Moorgate", "Platform 1", "outbound", 100),
+            arrival("3", "Moorgate", "Platform 1", "outbound", 300),
+        ]));
+
+        assert_eq!(headways[0].median_secs, Some(150));
+    }
+
+    #[test]
+    fn a_long_wait_among_short_ones_is_a_gap() {
+        let headways = analyse(&feed(vec![
+            arrival("1", "Moorgate", "Platform 1", "outbound", 0),
+            arrival("2", "Moorgate", "Platform 1", "outbound", 180),
+            arrival("3", "Moorgate", "Platform 1", "outbound", 360),
+            arrival("4", "Moorgate", "Platform 1", "outbound", 1560),
+        ]));
+
+        assert_eq!(headways[0].gaps.len(), 1);
+        assert_eq!(headways[0].gaps[0].secs, 1200);
+        assert_eq!(headways[0].gaps[0].label(), "20-minute gap at Moorgate");
+    }
+
+    #[test]
+    fn a_train_coming_round_the_loop_again_only_counts_once() {
+        let headways = analyse(&feed(vec![
+            arrival("1", "Moorgate", "Platform 1", "outbound", 60),
+            arrival("2", "Moorgate", "Platform 1", "outbound", 240),
+            arrival("1", "Moorgate", "Platform 1", "outbound", 1860),
+        ]));
+
+        assert_eq!(headways[0].trains, 2);
+        assert_eq!(headways[0].longest_secs, Some(180));
+        assert!(headways[0].gaps.is_empty());
+    }
+
+    #[test]
+    fn without_a_direction_each_platform_is_kept_apart() {
+        let headways = analyse(&feed(vec![
+            arrival("1", "Moorgate", "Platform 1", "", 60),
+            arrival("2", "Moorgate", "Platform 2", "", 90),
+            arrival("3", "Moorgate", "Platform 1", "", 420),
+            arrival("4", "Moorgate", "Platform 2", "", 450),
+        ]));
+
+        assert_eq!(headways.len(), 1);
+        assert_eq!(headways[0].direction, "Unknown");
+        assert_eq!(headways[0].median_secs, Some(360));
+    }
+
+    #[test]
+    fn directions_are_analysed_separately() {
+        let headways = analyse(&feed(vec![
+            arrival("1", "Moorgate", "Platform 1", "outbound", 60),
+            arrival("2", "Moorgate", "Platform 2", "inbound", 90),
+            arrival("3", "Moorgate", "Platform 1", "outbound", 420),
+        ]));
+
+        let directions = headways.iter().map(|d| d.direction.as_str()).collect::<Vec<_>>();
+        assert_eq!(directions, ["Inbound", "Outbound"]);
+        assert_eq!(headways[0].median_secs, None);
+        assert_eq!(headways[1].median_secs, Some(360));
+    }
+}
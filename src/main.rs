@@ -1,4 +1,14 @@
+mod accuracy;
+mod alerts;
 mod app;
+mod config;
+mod diagram;
+mod headway;
+mod network;
+mod palette;
+mod schedule;
+mod severity;
+mod station_index;
 mod ui;
 
 use app::{run_app, App};
@@ -0,0 +1,150 @@
+use std::collections::{BTreeMap, BTreeSet};
+use reqwest::Client;
+
+use crate::app::{Line, RouteResponse};
+
+// grid cells across the network; stations snap to it so lines run straighter than on a street map
+const GRID: f64 = 80.0;
+// longitude shrinks with latitude; at London's a degree east is about 0.62 of a degree north
+const LON_SCALE: f64 = 0.62;
+
+// longitude and latitude as canvas x and y, with x and y distances comparable
+pub fn project(lon: f64, lat: f64) -> (f64, f64) {
+    (lon * LON_SCALE, lat)
+}
+
+// rough distance in metres, plenty for "nearby" within a city
+pub fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (ax, ay) = project(a.0, a.1);
+    let (bx, by) = project(b.0, b.1);
+    ((ax - bx).powi(2) + (ay - by).powi(2)).sqrt() * 111_000.0
+}
+
+#[derive(Debug, Clone)]
+pub struct NetworkStation {
+    pub name: String,
+    pub x: f64,
+    pub y: f64,
+    pub lon: f64,
+    pub lat: f64,
+    pub lines: BTreeSet<String>,
+}
+impl NetworkStation {
+    pub fn is_interchange(&self) -> bool {
+        self.lines.len() > 1
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NetworkLine {
+    pub id: String,
+    pub segments: Vec<(String, String)>,
+}
+
+// every tube line drawn from its route sequences, stations placed by coordinates snapped to a grid
+#[derive(Debug, Clone, Default)]
+pub struct NetworkMap {
+    pub lines: Vec<NetworkLine>,
+    pub stations: BTreeMap<String, NetworkStation>,
+    pub attempted: bool,
+}
+impl NetworkMap {
+    pub async fn load(client: &Client, line_ids: &[String]) -> NetworkMap {
+        let mut map = NetworkMap { attempted: true, ..NetworkMap::default() };
+        let mut coordinates = BTreeMap::new();
+
+        for id in line_ids {
+            let res = match client.get(format!("https://api.tfl.gov.uk/Line/{}/Route/Sequence/all", id)).send().await {
+                Ok(res) => match res.json::<RouteResponse>().await {
+                    Ok(r) => r,
+                    Err(_) => continue,
+                },
+                Err(_) => continue,
+            };
+            for station in &res.stations {
+                coordinates.insert(station.id.clone(), (station.name.trim_end_matches(" Underground Station").to_string(), station.lon, station.lat));
+            }
+
+            let segments = res.orderedLineRoutes
+                .iter()
+                .flat_map(|r| r.naptanIds.windows(2).map(|w| match w[0] < w[1] {
+                    true => (w[0].clone(), w[1].clone()),
+                    false => (w[1].clone(), w[0].clone()),
+                }))
+                .collect::<BTreeSet<_>>();
+            for (a, b) in &segments {
+                for stop in [a, b] {
+                    map.stations
+                        .entry(stop.clone())
+                        .or_insert_with(|| NetworkStation { name: String::new(), x: 0.0, y: 0.0, lon: 0.0, lat: 0.0, lines: BTreeSet::new() })
+                        .lines
+                        .insert(id.clone());
+                }
+            }
+            map.lines.push(NetworkLine { id: id.clone(), segments: segments.into_iter().collect() });
+        }
+
+        let points = coordinates.values().map(|(_, lon, lat)| project(*lon, *lat)).collect::<Vec<_>>();
+        let (min_x, max_x) = points.iter().fold((f64::MAX, f64::MIN), |(lo, hi), (x, _)| (lo.min(*x), hi.max(*x)));
+        let (min_y, max_y) = points.iter().fold((f64::MAX, f64::MIN), |(lo, hi), (_, y)| (lo.min(*y), hi.max(*y)));
+        let cell = (max_x - min_x).max(max_y - min_y) / GRID;
+        for (id, station) in map.stations.iter_mut() {
+            if let Some((name, lon, lat)) = coordinates.get(id) {
+                let (x, y) = project(*lon, *lat);
+                station.name = name.clone();
+                station.lon = *lon;
+                station.lat = *lat;
+                station.x = ((x - min_x) / cell).round();
+                station.y = ((y - min_y) / cell).round();
+            }
+        }
+        map
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    // shown in place of an empty map: it is either still to be fetched or the fetch failed
+    pub fn placeholder(&self) -> &'static str {
+        match self.attempted {
+            true => "failed to load, r to retry",
+            false => "loading",
+        }
+    }
+
+    // projected extent of the stations with coordinates, as (min x, min y, max x, max y)
+    pub fn geo_extent(&self) -> (f64, f64, f64, f64) {
+        self.stations
+            .values()
+            .filter(|s| s.lat != 0.0)
+            .map(|s| project(s.lon, s.lat))
+            .fold((f64::MAX, f64::MAX, f64::MIN, f64::MIN), |(x0, y0, x1, y1), (x, y)| (x0.min(x), y0.min(y), x1.max(x), y1.max(y)))
+    }
+
+    pub fn bounds(&self) -> ([f64; 2], [f64; 2]) {
+        let xs = self.stations.values().map(|s| s.x);
+        let ys = self.stations.values().map(|s| s.y);
+        (
+            [xs.clone().fold(f64::MAX, f64::min) - 1.0, xs.fold(f64::MIN, f64::max) + 1.0],
+            [ys.clone().fold(f64::MAX, f64::min) - 1.0, ys.fold(f64::MIN, f64::max) + 1.0],
+        )
+    }
+}
+
+// stops a line's current statuses single out; an empty set for a disrupted line means all of it
+pub fn disrupted_stops(line: &Line) -> Option<BTreeSet<String>> {
+    let disrupted = line.lineStatuses
+        .iter()
+        .flatten()
+        .filter(|s| s.severity().is_disrupted())
+        .collect::<Vec<_>>();
+    match disrupted.is_empty() {
+        true => None,
+        false => Some(disrupted
+            .iter()
+            .filter_map(|s| s.disruption.as_ref())
+            .flat_map(|d| d.affectedStops.iter().map(|s| s.naptanId.clone()))
+            .collect()),
+    }
+}
@@ -0,0 +1,65 @@
+use std::env;
+use serde_derive::{Serialize, Deserialize};
+use tui::style::Color;
+
+use crate::severity::Severity;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    Auto,
+    TrueColor,
+    Basic,
+}
+
+// official TfL line colours, each with its own basic colour picked by hand, as the nearest ones
+// would leave several red lines and several grey ones
+const LINES: [(&str, (u8, u8, u8), Color); 15] = [
+    ("bakerloo", (179, 99, 5), Color::Yellow),
+    ("central", (227, 32, 23), Color::Red),
+    ("circle", (255, 211, 0), Color::LightYellow),
+    ("district", (0, 120, 42), Color::Green),
+    ("hammersmith-city", (243, 169, 187), Color::LightMagenta),
+    ("jubilee", (160, 165, 169), Color::Gray),
+    ("metropolitan", (155, 0, 86), Color::Magenta),
+    // officially black, which would vanish on a dark terminal
+    ("northern", (255, 255, 255), Color::White),
+    ("piccadilly", (0, 54, 136), Color::Blue),
+    ("victoria", (0, 152, 212), Color::LightBlue),
+    ("waterloo-city", (149, 205, 186), Color::LightCyan),
+    ("dlr", (0, 175, 173), Color::Cyan),
+    ("london-overground", (238, 124, 14), Color::LightRed),
+    ("elizabeth", (105, 80, 161), Color::DarkGray),
+    ("tram", (132, 184, 23), Color::LightGreen),
+];
+
+// line and severity colours, or their hand-picked basic ones where the terminal has no true colour
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
+    true_color: bool,
+}
+impl Palette {
+    pub fn new(mode: ColorMode) -> Palette {
+        let true_color = match mode {
+            ColorMode::TrueColor => true,
+            ColorMode::Basic => false,
+            ColorMode::Auto => env::var("COLORTERM").is_ok_and(|c| c == "truecolor" || c == "24bit"),
+        };
+        Palette { true_color }
+    }
+
+    pub fn line(&self, id: &str) -> Color {
+        match LINES.iter().find(|(line, _, _)| *line == id) {
+            Some((_, (r, g, b), _)) if self.true_color => Color::Rgb(*r, *g, *b),
+            Some((_, _, basic)) => *basic,
+            None => Color::Gray,
+        }
+    }
+
+    pub fn severity(&self, severity: Severity) -> Color {
+        match self.true_color {
+            true => severity.color(),
+            false => severity.basic_color(),
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use chrono::{DateTime, Datelike, Timelike, Weekday};
+use chrono_tz::{Europe::London, Tz};
+use serde::{Deserialize, Deserializer};
+use serde_derive::{Serialize, Deserialize};
+
+// the timetable API mixes "5" and 5 for hours and minutes
+fn number_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+    #[derive(serde_derive::Deserialize)]
+    #[serde(untagged)]
+    enum NumberOrString {
+        Number(i32),
+        String(String),
+    }
+    match NumberOrString::deserialize(deserializer)? {
+        NumberOrString::Number(n) => Ok(n),
+        NumberOrString::String(s) => s.trim().parse().map_err(serde::de::Error::custom),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct TimeOfDay {
+    #[serde(deserialize_with = "number_or_string")]
+    pub hour: i32,
+    #[serde(deserialize_with = "number_or_string")]
+    pub minute: i32,
+}
+impl TimeOfDay {
+    // minutes since the start of the service day; hours past 24 are after midnight
+    pub fn minutes(&self) -> i32 {
+        self.hour * 60 + self.minute
+    }
+    pub fn label(&self) -> String {
+        format!("{:02}:{:02}", self.hour % 24, self.minute)
+    }
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Frequency {
+    pub lowestFrequency: f64,
+    pub highestFrequency: f64,
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Period {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub fromTime: TimeOfDay,
+    pub toTime: TimeOfDay,
+    pub frequency: Option<Frequency>,
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Schedule {
+    pub name: String,
+    pub firstJourney: Option<TimeOfDay>,
+    pub lastJourney: Option<TimeOfDay>,
+    #[serde(default)]
+    pub periods: Vec<Period>,
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimetableRoute {
+    #[serde(default)]
+    pub schedules: Vec<Schedule>,
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Timetable {
+    #[serde(default)]
+    pub routes: Vec<TimetableRoute>,
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimetableStation {
+    pub id: String,
+    pub name: String,
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimetableResponse {
+    pub lineId: String,
+    #[serde(default)]
+    pub stations: Vec<TimetableStation>,
+    pub timetable: Option<Timetable>,
+}
+
+// first/last trains and frequency bands for one line in one direction
+#[derive(Debug, Clone)]
+pub struct ScheduledDirection {
+    pub towards: String,
+    pub schedule_name: String,
+    pub first: Option<TimeOfDay>,
+    pub last: Option<TimeOfDay>,
+    pub bands: Vec<Period>,
+}
+impl ScheduledDirection {
+    pub fn from_response(towards: String, response: &TimetableResponse) -> Option<ScheduledDirection> {
+        let (day, _) = service_day_now();
+        let schedules = response.timetable
+            .as_ref()?
+            .routes
+            .iter()
+            .flat_map(|r| r.schedules.iter())
+            .collect::<Vec<_>>();
+        let schedule = schedules
+            .iter()
+            .find(|s| runs_on(&s.name, day))
+            .or_else(|| schedules.first())?;
+        Some(ScheduledDirection {
+            towards,
+            schedule_name: schedule.name.clone(),
+            first: schedule.firstJourney,
+            last: schedule.lastJourney,
+            bands: schedule.periods.clone(),
+        })
+    }
+
+    // minutes until the last train leaves, or None once it has gone
+    pub fn minutes_to_last(&self) -> Option<i32> {
+        self.minutes_to_last_at(service_day_now().1)
+    }
+    fn minutes_to_last_at(&self, now: i32) -> Option<i32> {
+        let last = self.last?.minutes();
+        match last >= now {
+            true => Some(last - now),
+            false => None,
+        }
+    }
+}
+
+// the service day and minutes into it; trains before 04:00 belong to the previous day
+pub fn service_day_now() -> (Weekday, i32) {
+    service_day_at(chrono::Utc::now().with_timezone(&London))
+}
+fn service_day_at(now: DateTime<Tz>) -> (Weekday, i32) {
+    let minutes = (now.hour() * 60 + now.minute()) as i32;
+    match minutes < 4 * 60 {
+        true => (now.weekday().pred(), minutes + 24 * 60),
+        false => (now.weekday(), minutes),
+    }
+}
+
+// schedule names look like "Monday - Thursday", "Friday" or "Saturday and Sunday"
+fn runs_on(name: &str, day: Weekday) -> bool {
+    let days = name
+        .split(|c: char| !c.is_alphabetic())
+        .filter_map(|w| w.parse::<Weekday>().ok())
+        .collect::<Vec<_>>();
+    match (days.first(), name.contains('-') && days.len() == 2) {
+        (Some(from), true) => {
+            let (from, to, day) = (from.num_days_from_monday(), days[1].num_days_from_monday(), day.num_days_from_monday());
+            from <= day && day <= to
+        }
+        _ => days.contains(&day),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn time(hour: i32, minute: i32) -> TimeOfDay {
+        TimeOfDay { hour, minute }
+    }
+
+    fn last_at(last: TimeOfDay) -> ScheduledDirection {
+        ScheduledDirection {
+            towards: String::from("Brixton"),
+            schedule_name: String::from("Monday - Thursday"),
+            first: None,
+            last: Some(last),
+            bands: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn ranges_cover_the_days_between() {
+        assert!(runs_on("Monday - Thursday", Weekday::Mon));
+        assert!(runs_on("Monday - Thursday", Weekday::Wed));
+        assert!(runs_on("Monday - Thursday", Weekday::Thu));
+        assert!(!runs_on("Monday - Thursday", Weekday::Fri));
+        assert!(!runs_on("Monday - Thursday", Weekday::Sun));
+    }
+
+    #[test]
+    fn lists_cover_only_the_days_named() {
+        assert!(runs_on("Saturday and Sunday", Weekday::Sat));
+        assert!(runs_on("Saturday and Sunday", Weekday::Sun));
+        assert!(!runs_on("Saturday and Sunday", Weekday::Mon));
+        assert!(runs_on("Friday", Weekday::Fri));
+        assert!(!runs_on("Friday", Weekday::Sat));
+        assert!(!runs_on("Bank Holiday", Weekday::Mon));
+    }
+
+    #[test]
+    fn before_four_belongs_to_the_previous_service_day() {
+        // Saturday 1 June 2024
+        assert_eq!(service_day_at(London.ymd(2024, 6, 1).and_hms(0, 30, 0)), (Weekday::Fri, 24 * 60 + 30));
+        assert_eq!(service_day_at(London.ymd(2024, 6, 1).and_hms(3, 59, 0)), (Weekday::Fri, 27 * 60 + 59));
+        assert_eq!(service_day_at(London.ymd(2024, 6, 1).and_hms(4, 0, 0)), (Weekday::Sat, 4 * 60));
+        // Monday's small hours are still Sunday
+        assert_eq!(service_day_at(London.ymd(2024, 6, 3).and_hms(1, 0, 0)).0, Weekday::Sun);
+    }
+
+    #[test]
+    fn last_trains_after_midnight_count_past_24() {
+        assert_eq!(time(24, 35).minutes(), 24 * 60 + 35);
+        assert_eq!(time(24, 35).label(), "00:35");
+
+        let last = last_at(time(24, 35));
+        assert_eq!(last.minutes_to_last_at(23 * 60 + 50), Some(45));
+        assert_eq!(last.minutes_to_last_at(24 * 60 + 10), Some(25));
+        assert_eq!(last.minutes_to_last_at(24 * 60 + 35), Some(0));
+        assert_eq!(last.minutes_to_last_at(24 * 60 + 40), None);
+    }
+
+    #[test]
+    fn the_last_train_is_gone_once_its_time_has_passed() {
+        let last = last_at(time(23, 30));
+        assert_eq!(last.minutes_to_last_at(22 * 60), Some(90));
+        assert_eq!(last.minutes_to_last_at(23 * 60 + 31), None);
+        assert_eq!(last_at(time(23, 30)).minutes_to_last_at(24 * 60 + 5), None);
+        assert_eq!(ScheduledDirection { last: None, ..last_at(time(0, 0)) }.minutes_to_last_at(600), None);
+    }
+}
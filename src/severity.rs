@@ -0,0 +1,216 @@
+use std::cmp::Ordering;
+use serde_derive::{Serialize, Deserialize};
+use tui::style::Color;
+
+// one entry of https://api.tfl.gov.uk/Line/Meta/Severity
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SeverityMeta {
+    pub modeName: String,
+    pub severityLevel: i32,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    SpecialService,
+    Closed,
+    Suspended,
+    PartSuspended,
+    PlannedClosure,
+    PartClosure,
+    SevereDelays,
+    ReducedService,
+    BusService,
+    MinorDelays,
+    GoodService,
+    PartClosed,
+    ExitOnly,
+    NoStepFreeAccess,
+    ChangeOfFrequency,
+    Diverted,
+    NotRunning,
+    IssuesReported,
+    NoIssues,
+    Information,
+    ServiceClosed,
+    Unknown(i32),
+}
+impl Severity {
+    pub const ALL: [Severity; 21] = [
+        Severity::SpecialService,
+        Severity::Closed,
+        Severity::Suspended,
+        Severity::PartSuspended,
+        Severity::PlannedClosure,
+        Severity::PartClosure,
+        Severity::SevereDelays,
+        Severity::ReducedService,
+        Severity::BusService,
+        Severity::MinorDelays,
+        Severity::GoodService,
+        Severity::PartClosed,
+        Severity::ExitOnly,
+        Severity::NoStepFreeAccess,
+        Severity::ChangeOfFrequency,
+        Severity::Diverted,
+        Severity::NotRunning,
+        Severity::IssuesReported,
+        Severity::NoIssues,
+        Severity::Information,
+        Severity::ServiceClosed,
+    ];
+
+    pub fn from_level(level: i32) -> Severity {
+        match level {
+            0 => Severity::SpecialService,
+            1 => Severity::Closed,
+            2 => Severity::Suspended,
+            3 => Severity::PartSuspended,
+            4 => Severity::PlannedClosure,
+            5 => Severity::PartClosure,
+            6 => Severity::SevereDelays,
+            7 => Severity::ReducedService,
+            8 => Severity::BusService,
+            9 => Severity::MinorDelays,
+            10 => Severity::GoodService,
+            11 => Severity::PartClosed,
+            12 => Severity::ExitOnly,
+            13 => Severity::NoStepFreeAccess,
+            14 => Severity::ChangeOfFrequency,
+            15 => Severity::Diverted,
+            16 => Severity::NotRunning,
+            17 => Severity::IssuesReported,
+            18 => Severity::NoIssues,
+            19 => Severity::Information,
+            20 => Severity::ServiceClosed,
+            l => Severity::Unknown(l),
+        }
+    }
+
+    pub fn level(&self) -> i32 {
+        match self {
+            Severity::Unknown(l) => *l,
+            s => Severity::ALL.iter().position(|a| a == s).unwrap() as i32,
+        }
+    }
+
+    // fallback description, used when Line/Meta/Severity could not be loaded
+    pub fn description(&self) -> String {
+        match self {
+            Severity::SpecialService => "Special Service",
+            Severity::Closed => "Closed",
+            Severity::Suspended => "Suspended",
+            Severity::PartSuspended => "Part Suspended",
+            Severity::PlannedClosure => "Planned Closure",
+            Severity::PartClosure => "Part Closure",
+            Severity::SevereDelays => "Severe Delays",
+            Severity::ReducedService => "Reduced Service",
+            Severity::BusService => "Bus Service",
+            Severity::MinorDelays => "Minor Delays",
+            Severity::GoodService => "Good Service",
+            Severity::PartClosed => "Part Closed",
+            Severity::ExitOnly => "Exit Only",
+            Severity::NoStepFreeAccess => "No Step Free Access",
+            Severity::ChangeOfFrequency => "Change of frequency",
+            Severity::Diverted => "Diverted",
+            Severity::NotRunning => "Not Running",
+            Severity::IssuesReported => "Issues Reported",
+            Severity::NoIssues => "No Issues",
+            Severity::Information => "Information",
+            Severity::ServiceClosed => "Service Closed",
+            Severity::Unknown(_) => "Unknown",
+        }
+        .to_string()
+    }
+
+    // 0 is the worst; severities sort worst first
+    pub fn rank(&self) -> u8 {
+        match self {
+            Severity::Suspended => 0,
+            Severity::Closed => 1,
+            Severity::ServiceClosed => 2,
+            Severity::NotRunning => 3,
+            Severity::PlannedClosure => 4,
+            Severity::PartSuspended => 5,
+            Severity::PartClosure => 6,
+            Severity::PartClosed => 7,
+            Severity::SevereDelays => 8,
+            Severity::BusService => 9,
+            Severity::SpecialService => 10,
+            Severity::ReducedService => 11,
+            Severity::Diverted => 12,
+            Severity::MinorDelays => 13,
+            Severity::IssuesReported => 14,
+            Severity::ChangeOfFrequency => 15,
+            Severity::ExitOnly => 16,
+            Severity::NoStepFreeAccess => 17,
+            Severity::Information => 18,
+            Severity::Unknown(_) => 19,
+            Severity::NoIssues => 20,
+            Severity::GoodService => 21,
+        }
+    }
+
+    pub fn is_disrupted(&self) -> bool {
+        self.rank() < Severity::Information.rank()
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Severity::Suspended => Color::Rgb(220, 36, 31),
+            Severity::Closed => Color::Rgb(178, 34, 34),
+            Severity::ServiceClosed => Color::Rgb(139, 0, 0),
+            Severity::NotRunning => Color::Rgb(255, 69, 0),
+            Severity::PlannedClosure => Color::Rgb(199, 21, 133),
+            Severity::PartSuspended => Color::Rgb(255, 99, 71),
+            Severity::PartClosure => Color::Rgb(218, 112, 214),
+            Severity::PartClosed => Color::Rgb(186, 85, 211),
+            Severity::SevereDelays => Color::Rgb(255, 140, 0),
+            Severity::BusService => Color::Rgb(30, 144, 255),
+            Severity::SpecialService => Color::Rgb(100, 149, 237),
+            Severity::ReducedService => Color::Rgb(255, 215, 0),
+            Severity::Diverted => Color::Rgb(0, 206, 209),
+            Severity::MinorDelays => Color::Rgb(240, 230, 140),
+            Severity::IssuesReported => Color::Rgb(244, 164, 96),
+            Severity::ChangeOfFrequency => Color::Rgb(135, 206, 235),
+            Severity::ExitOnly => Color::Rgb(188, 143, 143),
+            Severity::NoStepFreeAccess => Color::Rgb(169, 169, 169),
+            Severity::Information => Color::Rgb(176, 196, 222),
+            Severity::Unknown(_) => Color::Rgb(128, 128, 128),
+            Severity::NoIssues => Color::Rgb(144, 238, 144),
+            Severity::GoodService => Color::Rgb(50, 205, 50),
+        }
+    }
+
+    // picked by hand from the 15 basic colours that show on a dark terminal; with more severities
+    // than colours, only ones that mean much the same share
+    pub fn basic_color(&self) -> Color {
+        match self {
+            Severity::Suspended => Color::LightRed,
+            Severity::Closed | Severity::ServiceClosed => Color::Red,
+            Severity::NotRunning => Color::DarkGray,
+            Severity::PlannedClosure => Color::Magenta,
+            Severity::PartSuspended => Color::LightMagenta,
+            Severity::PartClosure | Severity::PartClosed => Color::Blue,
+            Severity::SevereDelays => Color::Yellow,
+            Severity::BusService => Color::LightBlue,
+            Severity::SpecialService => Color::Green,
+            Severity::ReducedService | Severity::ChangeOfFrequency => Color::Cyan,
+            Severity::Diverted => Color::LightCyan,
+            Severity::MinorDelays | Severity::IssuesReported => Color::LightYellow,
+            Severity::ExitOnly | Severity::NoStepFreeAccess => Color::Gray,
+            Severity::Information | Severity::Unknown(_) => Color::White,
+            Severity::NoIssues | Severity::GoodService => Color::LightGreen,
+        }
+    }
+}
+impl PartialOrd for Severity {
+    fn partial_cmp(&self, other: &Severity) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Severity {
+    fn cmp(&self, other: &Severity) -> Ordering {
+        self.rank().cmp(&other.rank()).then(self.level().cmp(&other.level()))
+    }
+}
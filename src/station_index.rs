@@ -0,0 +1,350 @@
+use std::{fs, io, path::PathBuf};
+use reqwest::Client;
+use serde_derive::{Serialize, Deserialize};
+
+use crate::app::StopPoint;
+
+// stop types that are stations or bus stops rather than entrances, platforms or access areas
+const STATION_TYPES: [&str; 3] = ["NaptanMetroStation", "NaptanRailStation", "NaptanPublicBusCoachTram"];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdditionalProperty {
+    pub key: String,
+    pub value: String,
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModeStopPoint {
+    pub naptanId: String,
+    pub commonName: String,
+    pub stopType: String,
+    #[serde(default)]
+    pub lat: f64,
+    #[serde(default)]
+    pub lon: f64,
+    #[serde(default)]
+    pub stopLetter: Option<String>,
+    #[serde(default)]
+    pub modes: Vec<String>,
+    #[serde(default)]
+    pub additionalProperties: Vec<AdditionalProperty>,
+}
+impl ModeStopPoint {
+    fn property(&self, key: &str) -> String {
+        self.additionalProperties
+            .iter()
+            .find(|p| p.key == key)
+            .map(|p| p.value.clone())
+            .unwrap_or_default()
+    }
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModeStopPointResponse {
+    pub stopPoints: Vec<ModeStopPoint>,
+    #[serde(default)]
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IndexedStation {
+    pub id: String,
+    pub name: String,
+    pub zone: String,
+    pub lat: f64,
+    pub lon: f64,
+    #[serde(default)]
+    pub modes: Vec<String>,
+    #[serde(default)]
+    pub stop_letter: String,
+    #[serde(default)]
+    pub towards: String,
+    #[serde(skip)]
+    tokens: Vec<String>,
+}
+impl From<&IndexedStation> for StopPoint {
+    fn from(s: &IndexedStation) -> StopPoint {
+        StopPoint {
+            zone: s.zone.clone(),
+            id: s.id.clone(),
+            name: s.name.clone(),
+            modes: s.modes.clone(),
+            stopLetter: s.stop_letter.clone(),
+            towards: s.towards.clone(),
+            lat: s.lat,
+            lon: s.lon,
+        }
+    }
+}
+
+// every station in the enabled modes, kept on disk so lookups work offline
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StationIndex {
+    pub modes: Vec<String>,
+    pub stations: Vec<IndexedStation>,
+}
+impl StationIndex {
+    pub fn path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|d| d.join("ttfl").join("stations.json"))
+    }
+
+    // read the index from disk, building it from StopPoint/Mode when missing or built for other modes;
+    // None when the build failed
+    pub async fn load_or_build(client: &Client, modes: &[String]) -> Option<StationIndex> {
+        let on_disk = StationIndex::path()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|s| serde_json::from_str::<StationIndex>(&s).ok())
+            .filter(|i| i.modes == modes);
+        let mut index = match on_disk {
+            Some(i) => i,
+            None => {
+                let i = StationIndex::build(client, modes).await?;
+                let _ = i.save();
+                i
+            }
+        };
+        for station in &mut index.stations {
+            station.tokens = normalise(&station.name);
+        }
+        Some(index)
+    }
+
+    async fn build(client: &Client, modes: &[String]) -> Option<StationIndex> {
+        // large modes such as bus come back a page at a time
+        let mut stop_points = Vec::new();
+        for page in 1.. {
+            let res = client.get(format!("https://api.tfl.gov.uk/StopPoint/Mode/{}?page={}", modes.join(","), page))
+                .send()
+                .await
+                .ok()?
+                .json::<ModeStopPointResponse>()
+                .await
+                .ok()?;
+            let done = res.stopPoints.is_empty() || stop_points.len() + res.stopPoints.len() >= res.total;
+            stop_points.extend(res.stopPoints);
+            if done {
+                break;
+            }
+        }
+        Some(StationIndex::from_stop_points(modes, stop_points))
+    }
+
+    // the stations among the stop points, once each, by name
+    fn from_stop_points(modes: &[String], stop_points: Vec<ModeStopPoint>) -> StationIndex {
+        let mut stations = stop_points
+            .into_iter()
+            .filter(|s| STATION_TYPES.contains(&s.stopType.as_str()))
+            .map(|s| IndexedStation {
+                zone: s.property("Zone"),
+                towards: s.property("Towards"),
+                stop_letter: s.stopLetter.clone().unwrap_or_default(),
+                id: s.naptanId,
+                name: s.commonName,
+                lat: s.lat,
+                lon: s.lon,
+                modes: s.modes,
+                tokens: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        // a stop served by several modes comes back once per mode
+        stations.sort_by(|a, b| a.id.cmp(&b.id));
+        stations.dedup_by(|a, b| a.id == b.id);
+        stations.sort_by(|a, b| a.name.cmp(&b.name));
+
+        StationIndex { modes: modes.to_vec(), stations }
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let path = match StationIndex::path() {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stations.is_empty()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&IndexedStation> {
+        self.stations.iter().find(|s| s.id == id)
+    }
+
+    // best matches first; every word of the query has to match some word of the name
+    pub fn search(&self, query: &str, limit: usize) -> Vec<&IndexedStation> {
+        let query = normalise(query);
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut scored = self.stations
+            .iter()
+            .filter_map(|s| score(&query, &s.tokens).map(|score| (score, s)))
+            .collect::<Vec<_>>();
+        scored.sort_by(|(a, sa), (b, sb)| b.cmp(a).then(sa.tokens.len().cmp(&sb.tokens.len())));
+        scored.into_iter().take(limit).map(|(_, s)| s).collect()
+    }
+}
+
+// lower case words with punctuation, filler and common abbreviations folded away,
+// so "King's Cross St. Pancras Underground Station" and "kings x st pancras" agree
+fn normalise(name: &str) -> Vec<String> {
+    name.to_lowercase()
+        .replace(['\'', '’', '.'], "")
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .filter(|w| !matches!(*w, "underground" | "station" | "and" | "rail" | "dlr"))
+        .map(|w| match w {
+            "street" | "saint" => "st",
+            "cross" => "x",
+            "road" => "rd",
+            "wood" => "wd",
+            "park" => "pk",
+            "square" => "sq",
+            "lane" => "ln",
+            "green" => "grn",
+            "junction" => "jn",
+            "court" => "ct",
+            w => w,
+        }.to_string())
+        .collect()
+}
+
+fn score(query: &[String], tokens: &[String]) -> Option<u32> {
+    let mut total = 0;
+    for (i, q) in query.iter().enumerate() {
+        let best = tokens
+            .iter()
+            .enumerate()
+            .map(|(j, t)| {
+                let s = match () {
+                    _ if t == q => 4,
+                    _ if t.starts_with(q.as_str()) => 3,
+                    _ if q.len() >= 4 && levenshtein(q, t) <= 1 + q.len() / 7 => 2,
+                    _ => 0,
+                };
+                // a little extra for words in the same position
+                match s > 0 && i == j {
+                    true => s + 1,
+                    false => s,
+                }
+            })
+            .max()
+            .unwrap_or(0);
+        if best == 0 {
+            return None;
+        }
+        total += best;
+    }
+    Some(total)
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = match ca == *cb {
+                true => previous,
+                false => 1 + previous.min(row[j]).min(row[j + 1]),
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_of(names: &[&str]) -> StationIndex {
+        let stations = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| IndexedStation {
+                id: format!("940GZZLU{}", i),
+                name: name.to_string(),
+                zone: String::new(),
+                lat: 0.0,
+                lon: 0.0,
+                modes: Vec::new(),
+                stop_letter: String::new(),
+                towards: String::new(),
+                tokens: normalise(name),
+            })
+            .collect();
+        StationIndex { modes: vec![String::from("tube")], stations }
+    }
+
+    fn names(results: Vec<&IndexedStation>) -> Vec<&str> {
+        results.into_iter().map(|s| s.name.as_str()).collect()
+    }
+
+    fn stop_point(id: &str, name: &str, stop_type: &str) -> ModeStopPoint {
+        ModeStopPoint {
+            naptanId: id.to_string(),
+            commonName: name.to_string(),
+            stopType: stop_type.to_string(),
+            lat: 0.0,
+            lon: 0.0,
+            stopLetter: None,
+            modes: Vec::new(),
+            additionalProperties: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn each_station_is_indexed_once_by_name() {
+        let index = StationIndex::from_stop_points(&[String::from("tube"), String::from("dlr")], vec![
+            stop_point("940GZZLUBNK", "Bank Underground Station", "NaptanMetroStation"),
+            stop_point("940GZZLUBXN", "Brixton Underground Station", "NaptanMetroStation"),
+            stop_point("9400ZZLUBNK1", "Bank Underground Station", "NaptanMetroEntrance"),
+            stop_point("940GZZDLBNK", "Bank DLR Station", "NaptanMetroStation"),
+            stop_point("940GZZLUBNK", "Bank Underground Station", "NaptanMetroStation"),
+        ]);
+        let ids = index.stations.iter().map(|s| s.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["940GZZDLBNK", "940GZZLUBNK", "940GZZLUBXN"]);
+    }
+
+    #[test]
+    fn normalise_folds_punctuation_filler_and_abbreviations() {
+        assert_eq!(normalise("King's Cross St. Pancras Underground Station"), vec!["kings", "x", "st", "pancras"]);
+        assert_eq!(normalise("St. John's Wood"), vec!["st", "johns", "wd"]);
+    }
+
+    #[test]
+    fn abbreviated_queries_find_the_station() {
+        let index = index_of(&["King's Cross St. Pancras Underground Station", "St. John's Wood Underground Station", "Kingsbury Underground Station"]);
+        assert_eq!(names(index.search("kings x", 5)), vec!["King's Cross St. Pancras Underground Station"]);
+        assert_eq!(names(index.search("st johns wd", 5)), vec!["St. John's Wood Underground Station"]);
+    }
+
+    #[test]
+    fn every_query_word_has_to_match() {
+        let index = index_of(&["Oxford Circus Underground Station", "Piccadilly Circus Underground Station"]);
+        assert_eq!(names(index.search("oxford circus", 5)), vec!["Oxford Circus Underground Station"]);
+        assert!(index.search("oxford bank", 5).is_empty());
+        assert!(index.search("", 5).is_empty());
+    }
+
+    #[test]
+    fn exact_words_rank_above_prefixes_and_typos() {
+        let index = index_of(&["Bankside Pier", "Bank Underground Station"]);
+        assert_eq!(names(index.search("bank", 5)), vec!["Bank Underground Station", "Bankside Pier"]);
+
+        let index = index_of(&["Paddington Underground Station"]);
+        assert_eq!(names(index.search("padington", 5)), vec!["Paddington Underground Station"]);
+    }
+
+    #[test]
+    fn levenshtein_counts_edits() {
+        assert_eq!(levenshtein("bank", "bank"), 0);
+        assert_eq!(levenshtein("padington", "paddington"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
+}
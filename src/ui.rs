@@ -3,12 +3,19 @@ use tui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Spans, Text},
-    widgets::{Block, BorderType, Borders, List, ListItem, ListState, Paragraph, Wrap, Tabs, canvas::Canvas},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap, Tabs, canvas::{Canvas, Line as CanvasLine, Points, Rectangle}},
     Frame, symbols,
 };
 use chrono::{DateTime, FixedOffset, TimeZone};
+use std::collections::BTreeMap;
 use unicode_width::UnicodeWidthStr;
-use crate::app::{App, Focus, InputMode, Arrival};
+use crate::app::{App, Focus, InputMode, BoardGrouping, Line, Station, TimetableView};
+use crate::accuracy;
+use crate::diagram::{self, LANE_HEIGHT, NODE_WIDTH};
+use crate::headway;
+use crate::network;
+use crate::schedule::TimeOfDay;
+use crate::severity::Severity;
 
 pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
 
@@ -16,8 +23,12 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
-        .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .constraints(match app.active_alert() {
+            Some(_) => [Constraint::Length(3), Constraint::Length(3), Constraint::Min(0)].as_ref(),
+            None => [Constraint::Length(3), Constraint::Min(0)].as_ref(),
+        })
         .split(f.size());
+    let body = chunks[chunks.len() - 1];
 
         // get tab names
         let titles = app.tab_titles.iter().map(|t| {
@@ -31,7 +42,11 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
 
         // create and render tabs
         let tabs = Tabs::new(titles)
-            .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title("Tabs"))
+            .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).title(match (&app.station_index_build, app.station_index_failed_at) {
+                (Some(_), _) => "Tabs (building the station index, which takes a while on first run)",
+                (None, Some(_)) => "Tabs (the station index failed to build, retrying in a minute)",
+                (None, None) => "Tabs",
+            }))
             .select(app.tab_index)
             .style(Style::default().fg(Color::White))
             .highlight_style(
@@ -41,6 +56,10 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
             );
         f.render_widget(tabs, chunks[0]);
 
+        if app.active_alert().is_some() {
+            draw_alert(f, app, chunks[1]);
+        }
+
         // check current tab
         match app.tab_index {
 
@@ -53,7 +72,7 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
                     //     .constraints([Constraint::Length(100), Constraint::Min(100)].as_ref())
                     //     .split(chunks[1]);
 
-                    draw_dashboard(f, app, chunks[1]);
+                    draw_dashboard(f, app, body);
                 }
             },
 
@@ -64,7 +83,7 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
                     let chunks = Layout::default()
                         .margin(0)
                         .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
-                        .split(chunks[1]);
+                        .split(body);
                     
                     // render input in first split
                     draw_input(f, app, chunks[0]);
@@ -75,26 +94,122 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
                         //     .direction(Direction::Horizontal)
                         //     .split(chunks[1]);
                 
-                        draw_timetable(f, app, chunks[1]);
+                        // the followed train sits beside the board
+                        let area = match app.followed {
+                            Some(_) => {
+                                let chunks = Layout::default()
+                                    .direction(Direction::Horizontal)
+                                    .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
+                                    .split(chunks[1]);
+                                draw_followed(f, app, chunks[1]);
+                                chunks[0]
+                            }
+                            None => chunks[1],
+                        };
+
+                        match app.timetable_view {
+                            TimetableView::Live => app.map_pan_limits = draw_timetable(f, app, area),
+                            TimetableView::Scheduled => draw_schedule(f, app, area),
+                        }
+                    }
+
+                    // suggestions drop down over the timetable
+                    if let InputMode::Insert = app.input_mode {
+                        draw_suggestions(f, app, chunks[0], chunks[1]);
                     }
                 }
             },
+
+            // Lines
+            2 => {
+                {
+                    // split into line list and line detail
+                    let chunks = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .margin(0)
+                        .constraints([Constraint::Percentage(25), Constraint::Percentage(75)].as_ref())
+                        .split(body);
+
+                    draw_lines(f, app, chunks[0]);
+                    draw_line_detail(f, app, chunks[1]);
+                }
+            },
+
+            // Watch
+            3 => {
+                draw_watch(f, app, body);
+            },
+
+            // Accuracy
+            4 => {
+                draw_accuracy(f, app, body);
+            },
+
+            // Network
+            5 => {
+                draw_network(f, app, body);
+            },
+
+            // Map
+            6 => {
+                app.geo_pan_limits = draw_map(f, app, body);
+            },
             _ => unreachable!()
         }
 }
 
-fn draw_timetable<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+fn draw_alert<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let alert = match app.active_alert() {
+        Some(a) => a,
+        None => return,
+    };
+
+    let banner = Paragraph::new(Spans::from(vec![
+        Span::styled(alert.at.format("%H:%M ").to_string(), Style::default().fg(Color::DarkGray)),
+        Span::styled(alert.line_name.clone(), Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(match alert.new_status.is_empty() {
+            true => format!(": {}", alert.old_status),
+            false => format!(": {} -> {}", alert.old_status, alert.new_status),
+        }),
+        Span::styled(match app.waiting_alerts() {
+            0 => String::from("  (x to dismiss)"),
+            n => format!("  (x for the next, {} more)", n),
+        }, Style::default().fg(Color::DarkGray)),
+    ]))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::LightRed))
+            .title("Alert"),
+    );
+    f.render_widget(banner, area);
+}
+
+// smallest useful height of a line row and width of a platform column on the timetable
+const LINE_ROW_HEIGHT: u16 = 8;
+const PLATFORM_COL_WIDTH: u16 = 24;
+
+// returns how far the line diagrams can be panned, for the keys to stay within
+fn draw_timetable<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) -> (f64, f64) {
+    let mut pan_limits = (0.0, 0.0);
     let station = match &app.this_StopTimetable.stop_point {
-        Some(s) => format!("for {}", s.name),
+        Some(s) => format!("for {}", s.label()),
         None => "".to_string()
     };
 
     let block = Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .title(Span::raw(format!("Timetable {}", station)));
+            .title(Span::raw(format!(
+                "Timetable {} (by {}, {} lines, j/k to scroll, [/] for more {}s)",
+                station,
+                app.board_grouping.label(),
+                app.this_StopTimetable.unique_lines.len(),
+                app.board_grouping.label(),
+            )));
 
-    match app.this_StopTimetable.arrivals.len() {
+    match app.this_StopTimetable.unique_lines.len() {
         0 => f.render_widget(block, area),
         _ => {
                 // 
@@ -111,30 +226,40 @@ fn draw_timetable<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
                     .constraints([Constraint::Percentage(100)].as_ref())
                     .split(chunks[0]);
 
-                //
-                let line_constraints = match app.this_StopTimetable.unique_lines.len() {
-                    0 => [Constraint::Percentage(0)].as_ref(),
-                    1 => [Constraint::Percentage(100)].as_ref(),
-                    2 => [Constraint::Percentage(50), Constraint::Percentage(50)].as_ref(),
-                    3 => [Constraint::Percentage(33), Constraint::Percentage(33), Constraint::Percentage(33)].as_ref(),
-                    4 => [Constraint::Percentage(25), Constraint::Percentage(25), Constraint::Percentage(25), Constraint::Percentage(25)].as_ref(),
-                    5 => [Constraint::Percentage(20), Constraint::Percentage(20), Constraint::Percentage(20), Constraint::Percentage(20), Constraint::Percentage(20)].as_ref(),
-                    _ => [Constraint::Percentage(33), Constraint::Percentage(33), Constraint::Percentage(33)].as_ref()
-                };
+                // only as many lines as fit, starting from the scroll position; branched lines need taller rows
+                let lines = app.this_StopTimetable.unique_lines.iter().collect::<Vec<_>>();
+                let first = app.timetable_scroll.min(lines.len().saturating_sub(1));
+                let mut heights = Vec::new();
+                for line in &lines[first..] {
+                    let height = row_height(app, line);
+                    if !heights.is_empty() && heights.iter().sum::<u16>() + height > chunks[0].height {
+                        break;
+                    }
+                    heights.push(height);
+                }
+                let visible = heights.len();
+                let total = heights.iter().map(|&h| h as u32).sum::<u32>();
 
-                // split into Line rows
+                // split into Line rows, sharing out any spare height
                 let rows = Layout::default()
                     .direction(Direction::Vertical)
-                    .constraints(line_constraints)
+                    .constraints(heights.iter().map(|&h| Constraint::Ratio(h as u32, total)).collect::<Vec<_>>())
                     .split(chunks[0]);
 
-                let mut row_count = 0;
-                for line in &app.this_StopTimetable.unique_lines {
+                for (row_count, &line) in lines.iter().skip(first).take(visible).enumerate() {
+                    let mut title = match app.this_StopTimetable.is_bus_route(line) {
+                        true => format!("Bus {}", line),
+                        false => line.to_string(),
+                    };
+                    // what the map cursor is on, on the line it moves along
+                    if let (Some(Focus::Map), Some(station)) = (&app.focus, app.cursor_station().filter(|_| app.cursor_line() == Some(line))) {
+                        title = format!("{} - {} (h/l to move, Enter to open)", title, station_summary(app, station));
+                    }
                     f.render_widget(Block::default()
-                            .title(line.clone())
+                            .title(title)
                             .borders(Borders::ALL)
                             .border_type(BorderType::Rounded)
-                            .border_style(Style::default().fg(Color::LightRed))
+                            .border_style(Style::default().fg(app.palette.line(line)))
                         ,rows[row_count]
                     );
 
@@ -148,26 +273,18 @@ fn draw_timetable<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
                             .split(rows[row_count]);
 
                         {
-                            let num_platforms = app.this_StopTimetable.unique_platforms[&line.clone()].len();
-                            let platform_constraints = match num_platforms {
-                                0 => [Constraint::Percentage(0)].as_ref(),
-                                1 => [Constraint::Percentage(100)].as_ref(),
-                                2 => [Constraint::Percentage(50), Constraint::Percentage(50)].as_ref(),
-                                3 => [Constraint::Percentage(33), Constraint::Percentage(33), Constraint::Percentage(33)].as_ref(),
-                                4 => [Constraint::Percentage(25), Constraint::Percentage(25), Constraint::Percentage(25), Constraint::Percentage(25)].as_ref(),
-                                5 => [Constraint::Percentage(20), Constraint::Percentage(20), Constraint::Percentage(20), Constraint::Percentage(20), Constraint::Percentage(20)].as_ref(),
-                                _ => [Constraint::Percentage(33), Constraint::Percentage(33), Constraint::Percentage(33)].as_ref()
-                            };
+                            let groups = app.this_StopTimetable.board_groups(line, app.board_grouping);
+                            let visible_cols = ((chunks[0].width / PLATFORM_COL_WIDTH) as usize).max(1).min(groups.len());
+                            let first_col = app.platform_scroll.min(groups.len() - visible_cols);
 
                             let cols = Layout::default()
                                 .direction(Direction::Horizontal)
-                                .constraints(platform_constraints)
+                                .constraints(vec![Constraint::Ratio(1, visible_cols.max(1) as u32); visible_cols])
                                 .split(chunks[0]);
 
-                            let mut col_count = 0;
-                            for platform in &app.this_StopTimetable.unique_platforms[&line.clone()] {
+                            for (col_count, platform) in groups.iter().skip(first_col).take(visible_cols).enumerate() {
                                 f.render_widget(Block::default()
-                                    .title(platform.clone())
+                                    .title(app.this_StopTimetable.group_title(platform, app.board_grouping))
                                     .borders(Borders::ALL)
                                     .border_type(BorderType::Rounded)
                                     .border_style(Style::default().fg(Color::LightYellow))
@@ -180,17 +297,26 @@ fn draw_timetable<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
                                         .constraints([Constraint::Percentage(100)].as_ref())
                                         .split(cols[col_count]);
 
-                                    let mut items = app.this_StopTimetable.arrivals
+                                    let mut arrivals = app.this_StopTimetable.arrivals
                                         .iter()
-                                        .enumerate()
-                                        .filter(|(_, a)| a.lineId == line.clone() && a.platformName == platform.clone())
-                                        .map(|(_, e)| format!("{} ---- {}", &e.timeToStation, &e.currentLocation))
+                                        .filter(|a| a.lineId == *line && a.board_group(app.board_grouping) == *platform)
                                         .collect::<Vec<_>>();
-                                    items.sort();
+                                    arrivals.sort_by_key(|a| a.timeToStation);
 
-                                    let items = items
+                                    let selected = app.selected_arrival();
+                                    let items = arrivals
                                         .iter()
-                                        .map(|a| ListItem::new(a.to_string()))
+                                        .map(|e| {
+                                            let text = match app.board_grouping {
+                                                BoardGrouping::Destination => format!("{} ---- {}", e.display_time(app.config.time_format), &e.platformName),
+                                                _ => format!("{} ---- {} ({})", e.display_time(app.config.time_format), e.destination(), &e.currentLocation),
+                                            };
+                                            match selected {
+                                                Some(s) if std::ptr::eq(s, *e) => ListItem::new(text)
+                                                    .style(Style::default().add_modifier(Modifier::BOLD).bg(Color::DarkGray)),
+                                                _ => ListItem::new(text),
+                                            }
+                                        })
                                         .collect::<Vec<_>>();
 
                                     let lines = List::new(items)
@@ -204,7 +330,6 @@ fn draw_timetable<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
                                         );
                                     f.render_widget(lines, chunks[0]);
                                 }
-                                col_count += 1;
                             };
                         }
 
@@ -224,49 +349,549 @@ fn draw_timetable<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
                         // );
                         // f.render_widget(all_stops, chunks[1]);
                         {
-                            let rows = Layout::default()
-                                .direction(Direction::Vertical)
-                                .constraints([Constraint::Percentage(25), Constraint::Percentage(25), Constraint::Percentage(25), Constraint::Percentage(25)].as_ref())
-                                .split(chunks[1]);
-
-                            // top row
-                            // middle row
+                            let lanes = app.this_StopTimetable.live_maps
+                                .get(line)
+                                .map(|m| m.lanes())
+                                .map_or(1, |(lowest, highest)| highest - lowest + 1);
+                            let nodes = app.this_StopTimetable.station_nodes.get(line).map(|n| &n[..]).unwrap_or_default();
+                            let (x_bounds, limits) = map_window(app, line, chunks[1].width);
+                            pan_limits = (f64::min(pan_limits.0, limits.0), f64::max(pan_limits.1, limits.1));
+                            let cells_per_station = chunks[1].width as f64 / (x_bounds.1 - x_bounds.0);
+                            let labels = station_labels(app, line, cells_per_station);
                             let canvas =  Canvas::default()
                                 .block(
                                     Block::default()
                                 )
                                 .paint(|ctx| {
-                                    for station_node in &app.this_StopTimetable.station_nodes[line][0] {
-                                        ctx.draw(&station_node.rect);
+                                    // track first, so stations and trains are drawn over it
+                                    for (a, b) in app.this_StopTimetable.live_maps.get(line).map(|m| &m.edges[..]).unwrap_or_default() {
+                                        let (a, b) = (&nodes[*a].rect, &nodes[*b].rect);
+                                        ctx.draw(&CanvasLine {
+                                            x1: a.x + a.width / 2.0,
+                                            y1: a.y + a.height / 2.0,
+                                            x2: b.x + b.width / 2.0,
+                                            y2: b.y + b.height / 2.0,
+                                            color: app.palette.line(line),
+                                        });
                                     }
-                                })
-                                .marker(symbols::Marker::Braille)
-                                .x_bounds([10.0, 110.0])
-                                .y_bounds([10.0, 110.0]);
-
-                            f.render_widget(canvas, rows[1]);
-
-                            let canvas =  Canvas::default()
-                                .block(
-                                    Block::default()
-                                )
-                                .paint(|ctx| {
-                                    for station_node in &app.this_StopTimetable.station_nodes[line][1] {
+                                    for station_node in nodes {
                                         ctx.draw(&station_node.rect);
                                     }
+                                    for train in train_markers(app, line) {
+                                        ctx.draw(&train);
+                                    }
+                                    if let (Some(Focus::Map), Some(cursor)) = (&app.focus, app.map_cursor.filter(|_| app.cursor_line() == Some(line))) {
+                                        if let Some(node) = nodes.get(cursor) {
+                                            ctx.draw(&Rectangle {
+                                                x: node.rect.x - NODE_WIDTH,
+                                                y: node.rect.y - LANE_HEIGHT / 16.0,
+                                                width: node.rect.width + 2.0 * NODE_WIDTH,
+                                                height: node.rect.height + LANE_HEIGHT / 8.0,
+                                                color: Color::LightMagenta,
+                                            });
+                                        }
+                                    }
+                                    for (x, y, label) in &labels {
+                                        ctx.print(*x, *y, label.clone());
+                                    }
                                 })
                                 .marker(symbols::Marker::Braille)
-                                .x_bounds([10.0, 110.0])
-                                .y_bounds([10.0, 110.0]);
+                                .x_bounds([x_bounds.0, x_bounds.1])
+                                .y_bounds([0.0, lanes as f64 * LANE_HEIGHT]);
 
-                            f.render_widget(canvas, rows[2]);
-                            // bottom row
+                            f.render_widget(canvas, chunks[1]);
                         }
                     }
-                    row_count += 1;
                 }
         }
     }
+    pan_limits
+}
+
+// the stretch of a line diagram on screen, centred on the current station once it no longer fits,
+// and how far it can be panned either way before it stops moving
+fn map_window(app: &App, line: &str, width: u16) -> ((f64, f64), (f64, f64)) {
+    let live_map = match app.this_StopTimetable.live_maps.get(line) {
+        Some(m) => m,
+        None => return ((-0.5, 0.5), (0.0, 0.0)),
+    };
+    let columns = live_map.stations.iter().map(|s| s.column).fold(0.0, f64::max) + 1.0;
+    // follow the map cursor while it is on this line
+    let cursor = match app.focus {
+        Some(Focus::Map) if app.cursor_line().map(|l| &l[..]) == Some(line) => app.map_cursor,
+        _ => None,
+    };
+    let centre = cursor
+        .or_else(|| app.this_StopTimetable.stop_point.as_ref().and_then(|s| live_map.station_index(&s.id)))
+        .map_or(columns / 2.0, |i| live_map.stations[i].column);
+    (
+        diagram::window(columns, width, app.map_zoom, centre, app.map_pan),
+        diagram::pan_limits(columns, width, app.map_zoom, centre),
+    )
+}
+
+// "Oxford Circus, zone 1, bakerloo central victoria"
+fn station_summary(app: &App, station: &Station) -> String {
+    let info = app.station_info.get(&station.naptan_id);
+    let mut parts = vec![match station.name.is_empty() {
+        true => station.naptan_id.clone(),
+        false => station.name.clone(),
+    }];
+    if let Some(info) = info {
+        if !info.zone.is_empty() {
+            parts.push(format!("zone {}", info.zone));
+        }
+        if !info.lines.is_empty() {
+            parts.push(info.lines.join(" "));
+        }
+    }
+    parts.join(", ")
+}
+
+// a line row fits the arrival boards and two text rows per lane of its diagram, inside borders and margin
+fn row_height(app: &App, line: &str) -> u16 {
+    let lanes = app.this_StopTimetable.live_maps
+        .get(line)
+        .map(|m| m.lanes())
+        .map_or(1, |(lowest, highest)| highest - lowest + 1);
+    LINE_ROW_HEIGHT.max(4 + 2 * lanes as u16)
+}
+
+// smallest label worth drawing, in cells
+const MIN_LABEL_WIDTH: f64 = 4.0;
+
+// station names for the live map, abbreviated to the room each node has, in the upper half of their lane;
+// when names would collide only every few stations are labelled, plus interchanges and the current one
+fn station_labels(app: &App, line: &str, cells_per_station: f64) -> Vec<(f64, f64, Span<'static>)> {
+    let (nodes, live_map) = match (app.this_StopTimetable.station_nodes.get(line), app.this_StopTimetable.live_maps.get(line)) {
+        (Some(n), Some(m)) if !n.is_empty() => (n, m),
+        _ => return Vec::new(),
+    };
+    let current = app.this_StopTimetable.stop_point.as_ref().map(|s| s.id.clone()).unwrap_or_default();
+
+    let every = (MIN_LABEL_WIDTH / cells_per_station).ceil().max(1.0) as usize;
+    let room = ((cells_per_station * every as f64) as usize).saturating_sub(1).max(MIN_LABEL_WIDTH as usize - 1);
+
+    // count stations along each lane, so every lane is thinned the same way
+    let mut order = (0..live_map.stations.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&live_map.stations[a], &live_map.stations[b]);
+        a.lane.cmp(&b.lane).then(a.column.partial_cmp(&b.column).unwrap_or(std::cmp::Ordering::Equal))
+    });
+    let mut nth_in_lane = vec![0; order.len()];
+    for (k, &i) in order.iter().enumerate() {
+        nth_in_lane[i] = match k.checked_sub(1).map(|p| order[p]) {
+            Some(p) if live_map.stations[p].lane == live_map.stations[i].lane => nth_in_lane[p] + 1,
+            _ => 0,
+        };
+    }
+
+    let mut labelled = nodes
+        .iter()
+        .zip(&live_map.stations)
+        .enumerate()
+        .filter(|(i, (_, s))| nth_in_lane[*i] % every == 0 || s.interchange || s.naptan_id == current)
+        .filter(|(_, (_, s))| !s.name.is_empty())
+        .collect::<Vec<_>>();
+    // the current station goes last so its full name is drawn over its neighbours
+    labelled.sort_by_key(|(_, (_, s))| s.naptan_id == current);
+
+    labelled
+        .into_iter()
+        .map(|(_, (node, s))| {
+            let (text, style) = match (s.naptan_id == current, s.interchange) {
+                (true, _) => (s.name.clone(), Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)),
+                (false, true) => (s.name.chars().take(room).collect(), Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+                (false, false) => (s.name.chars().take(room).collect(), Style::default().fg(Color::Gray)),
+            };
+            (node.rect.x, node.rect.y + LANE_HEIGHT / 2.0, Span::styled(text, style))
+        })
+        .collect()
+}
+
+// trains on the live map, at or between stations: above the track one way, below it the other
+fn train_markers(app: &App, line: &str) -> Vec<Rectangle> {
+    let (nodes, live_map) = match (app.this_StopTimetable.station_nodes.get(line), app.this_StopTimetable.live_maps.get(line)) {
+        (Some(n), Some(m)) => (n, m),
+        _ => return Vec::new(),
+    };
+
+    live_map.trains_currently_at
+        .iter()
+        .filter(|t| t.from < nodes.len() && t.to < nodes.len())
+        .map(|t| {
+            let (from, to) = (&nodes[t.from].rect, &nodes[t.to].rect);
+            let y = from.y + (to.y - from.y) * t.progress;
+            Rectangle {
+                x: from.x + (to.x - from.x) * t.progress,
+                y: match t.forward {
+                    true => y + from.height + LANE_HEIGHT / 16.0,
+                    false => y - LANE_HEIGHT / 16.0,
+                },
+                width: from.width,
+                height: 0.0,
+                color: match (app.followed.as_ref().is_some_and(|f| f.line_id == line && f.vehicle_id == t.train_key), t.at_station) {
+                    (true, _) => Color::LightMagenta,
+                    (false, true) => Color::LightCyan,
+                    (false, false) => Color::White,
+                },
+            }
+        })
+        .collect()
+}
+
+// upcoming stops of the followed train, with predicted times
+fn draw_followed<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let train = match &app.followed {
+        Some(t) => t,
+        None => return,
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::LightCyan))
+        .title(format!("Following {} to {} (u to stop)", train.line_id, train.destination));
+
+    let items = match train.stops.is_empty() {
+        true => vec![ListItem::new("No longer predicted")],
+        false => train.stops
+            .iter()
+            .map(|a| ListItem::new(format!(
+                "{} ---- {}",
+                a.display_time(app.config.time_format),
+                a.stationName.trim_end_matches(" Underground Station"),
+            )))
+            .collect(),
+    };
+    f.render_widget(List::new(items).block(block), area);
+}
+
+// how far countdowns were off, from trains logged leaving the boards
+fn draw_accuracy<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let log = &app.accuracy;
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(area);
+
+    let title = match &log.last_export {
+        _ if app.config.board_refresh_secs as i64 > accuracy::MAX_GAP_SECS => format!(
+            "board_refresh_secs is over {}, too slow to tell when trains arrive; nothing is logged",
+            accuracy::MAX_GAP_SECS,
+        ),
+        Some(status) => format!("{} arrivals logged, {} trains on the boards ({})", log.arrived.len(), log.tracked.len(), status),
+        None => format!("{} arrivals logged, {} trains on the boards (e to export)", log.arrived.len(), log.tracked.len()),
+    };
+
+    for (i, (heading, summaries)) in [("By line", log.by_line()), ("By station", log.by_station())].into_iter().enumerate() {
+        let mut items = vec![ListItem::new(Span::styled(
+            format!("{:<24} {:>6} {:>6} {:>8} {:>8} {:>7}", heading, "trains", "preds", "avg err", "bias", "<1 min"),
+            Style::default().add_modifier(Modifier::BOLD),
+        ))];
+        items.extend(summaries.iter().map(|s| ListItem::new(format!(
+            "{:<24} {:>6} {:>6} {:>7.0}s {:>+7.0}s {:>6.0}%",
+            s.name, s.trains, s.predictions, s.mean_abs_error, s.bias, s.within_a_minute,
+        ))));
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::LightCyan));
+        let block = match i {
+            0 => block.title(title.clone()),
+            _ => block.title("positive bias: trains arrived later than predicted"),
+        };
+        f.render_widget(List::new(items).block(block), chunks[i]);
+    }
+}
+
+// every tube line on one canvas, with disrupted sections in their severity colour
+fn draw_network<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let network = &app.network;
+    let disrupted = app.lineData
+        .iter()
+        .filter_map(|l| network::disrupted_stops(l).map(|stops| (l.id.clone(), (stops, l.worst_severity()))))
+        .collect::<BTreeMap<_, _>>();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .title(match network.is_empty() {
+            true => format!("Network ({})", network.placeholder()),
+            false => format!("Network ({} lines, {} disrupted, interchanges in white)", network.lines.len(), disrupted.len()),
+        });
+    if network.is_empty() {
+        f.render_widget(block, area);
+        return;
+    }
+
+    let (x_bounds, y_bounds) = network.bounds();
+    let canvas = Canvas::default()
+        .block(block)
+        .paint(|ctx| {
+            for line in &network.lines {
+                for (a, b) in &line.segments {
+                    let (a_station, b_station) = match (network.stations.get(a), network.stations.get(b)) {
+                        (Some(x), Some(y)) => (x, y),
+                        _ => continue,
+                    };
+                    // no affected stops listed means the whole line
+                    let color = match disrupted.get(&line.id) {
+                        Some((stops, severity)) if stops.is_empty() || (stops.contains(a) && stops.contains(b)) => app.palette.severity(*severity),
+                        _ => app.palette.line(&line.id),
+                    };
+                    ctx.draw(&CanvasLine { x1: a_station.x, y1: a_station.y, x2: b_station.x, y2: b_station.y, color });
+                }
+            }
+            ctx.layer();
+            for station in network.stations.values().filter(|s| s.is_interchange()) {
+                ctx.draw(&Rectangle { x: station.x - 0.3, y: station.y - 0.3, width: 0.6, height: 0.6, color: Color::White });
+            }
+        })
+        .marker(symbols::Marker::Braille)
+        .x_bounds(x_bounds)
+        .y_bounds(y_bounds);
+    f.render_widget(canvas, area);
+}
+
+// stops this close to the searched station are highlighted, the closest few by name
+const NEARBY_METRES: f64 = 800.0;
+const NEARBY_LABELS: usize = 6;
+
+// stations and lines where they really are, centred on the searched station
+// returns how far the map can be panned each way, in screens, before its centre leaves the stations
+fn draw_map<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) -> ((f64, f64), (f64, f64)) {
+    let network = &app.network;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .title(match network.is_empty() {
+            true => format!("Map ({})", network.placeholder()),
+            false => "Map (+/- to zoom, ,/. and j/k to pan, 0 to reset)".to_string(),
+        });
+    if network.is_empty() {
+        f.render_widget(block, area);
+        return ((0.0, 0.0), (0.0, 0.0));
+    }
+
+    let (x0, y0, x1, y1) = network.geo_extent();
+    if x0 > x1 {
+        f.render_widget(block, area);
+        return ((0.0, 0.0), (0.0, 0.0));
+    }
+    let searched = app.this_StopTimetable.stop_point.as_ref().filter(|s| s.lat != 0.0);
+    let (cx, cy) = match searched {
+        Some(s) => network::project(s.lon, s.lat),
+        None => ((x0 + x1) / 2.0, (y0 + y1) / 2.0),
+    };
+
+    // terminal cells are about twice as tall as they are wide
+    let aspect = area.width.saturating_sub(2).max(1) as f64 / (area.height.saturating_sub(2).max(1) as f64 * 2.0);
+    let height = (y1 - y0).max((x1 - x0) / aspect) * 1.05 / app.geo_zoom;
+    let width = height * aspect;
+    let limits = |centre: f64, low: f64, high: f64, size: f64| (((low - centre) / size).min(0.0), ((high - centre) / size).max(0.0));
+    let pan_limits = (limits(cx, x0, x1, width), limits(cy, y0, y1, height));
+    let (cx, cy) = (cx + app.geo_pan.0 * width, cy + app.geo_pan.1 * height);
+
+    // nearby stops from the offline index, which covers every enabled mode
+    let mut nearby = match searched {
+        Some(s) => app.station_index.stations
+            .iter()
+            .filter(|n| n.id != s.id && n.lat != 0.0)
+            .map(|n| (network::distance((s.lon, s.lat), (n.lon, n.lat)), n))
+            .filter(|(d, _)| *d <= NEARBY_METRES)
+            .collect::<Vec<_>>(),
+        None => Vec::new(),
+    };
+    nearby.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let stations = network.stations.values().filter(|s| s.lat != 0.0).map(|s| network::project(s.lon, s.lat)).collect::<Vec<_>>();
+    let nearby_points = nearby.iter().map(|(_, n)| network::project(n.lon, n.lat)).collect::<Vec<_>>();
+
+    let canvas = Canvas::default()
+        .block(block)
+        .paint(|ctx| {
+            for line in &network.lines {
+                for (a, b) in &line.segments {
+                    let (a, b) = match (network.stations.get(a), network.stations.get(b)) {
+                        (Some(a), Some(b)) if a.lat != 0.0 && b.lat != 0.0 => (network::project(a.lon, a.lat), network::project(b.lon, b.lat)),
+                        _ => continue,
+                    };
+                    ctx.draw(&CanvasLine { x1: a.0, y1: a.1, x2: b.0, y2: b.1, color: app.palette.line(&line.id) });
+                }
+            }
+            ctx.layer();
+            ctx.draw(&Points { coords: &stations, color: Color::Gray });
+            ctx.draw(&Points { coords: &nearby_points, color: Color::LightCyan });
+            if let Some(s) = searched {
+                let (x, y) = network::project(s.lon, s.lat);
+                ctx.draw(&Rectangle { x: x - width / 200.0, y: y - height / 100.0, width: width / 100.0, height: height / 50.0, color: Color::LightGreen });
+                for ((_, n), (x, y)) in nearby.iter().zip(&nearby_points).take(NEARBY_LABELS) {
+                    ctx.print(*x, *y, Span::styled(n.name.clone(), Style::default().fg(Color::LightCyan)));
+                }
+                ctx.print(x, y, Span::styled(s.name.clone(), Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)));
+            }
+        })
+        .marker(symbols::Marker::Braille)
+        .x_bounds([cx - width / 2.0, cx + width / 2.0])
+        .y_bounds([cy - height / 2.0, cy + height / 2.0]);
+    f.render_widget(canvas, area);
+    pan_limits
+}
+
+fn draw_watch<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let pinned = &app.config.pinned_stations;
+    if pinned.is_empty() {
+        let hint = Paragraph::new(Spans::from(vec![
+            Span::styled("Press ", Style::default().fg(Color::DarkGray)),
+            Span::styled("p", Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(" on the Timetable tab to pin a station here.", Style::default().fg(Color::DarkGray)),
+        ]))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .title("Watch"),
+        );
+        f.render_widget(hint, area);
+        return;
+    }
+
+    // side by side when there is room, stacked otherwise
+    let direction = match area.width / pinned.len() as u16 >= 40 {
+        true => Direction::Horizontal,
+        false => Direction::Vertical,
+    };
+    let boards = Layout::default()
+        .direction(direction)
+        .constraints(vec![Constraint::Ratio(1, pinned.len() as u32); pinned.len()])
+        .split(area);
+
+    for (id, board) in pinned.iter().zip(boards) {
+        let timetable = app.stop_cache.get(id);
+        let title = match timetable {
+            Some(t) => format!(
+                "{} ({}s ago)",
+                t.stop_point.as_ref().map(|s| s.label()).unwrap_or_else(|| id.clone()),
+                t.refreshed_at.map(|r| r.elapsed().as_secs()).unwrap_or(0),
+            ),
+            None => format!("{} (loading)", id),
+        };
+
+        let mut arrivals = timetable
+            .map(|t| t.arrivals.iter().collect::<Vec<_>>())
+            .unwrap_or_default();
+        arrivals.sort_by_key(|a| a.timeToStation);
+        let items = arrivals
+            .iter()
+            .map(|a| ListItem::new(format!(
+                "{} {} ---- {} ({})",
+                a.display_time(app.config.time_format),
+                a.lineId,
+                a.destination(),
+                a.platformName,
+            )))
+            .collect::<Vec<_>>();
+
+        f.render_widget(
+            List::new(items).block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(Color::LightYellow)),
+            ),
+            board,
+        );
+    }
+}
+
+fn draw_schedule<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let station = match &app.this_StopTimetable.stop_point {
+        Some(s) => format!("for {}", s.name),
+        None => "".to_string()
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .title(Span::raw(format!("Scheduled timetable {}", station)));
+
+    let mut text = Text::default();
+    for (line, directions) in &app.this_StopTimetable.schedules {
+        text.extend(Text::from(Spans::from(Span::styled(
+            line.clone(),
+            Style::default().fg(app.palette.line(line)).add_modifier(Modifier::BOLD),
+        ))));
+        if directions.is_empty() {
+            text.extend(Text::raw("  No timetable available"));
+        }
+        for direction in directions {
+            text.extend(Text::from(Spans::from(vec![
+                Span::raw(format!("  towards {} ", direction.towards)),
+                Span::styled(format!("({})", direction.schedule_name), Style::default().fg(Color::DarkGray)),
+            ])));
+
+            let time = |t: &Option<_>| t.as_ref().map(TimeOfDay::label).unwrap_or_else(|| "--:--".to_string());
+            let mut spans = vec![Span::raw(format!("    First {}  Last {}  ", time(&direction.first), time(&direction.last)))];
+            match direction.minutes_to_last() {
+                Some(m) if m <= app.config.last_train_warning_mins => spans.push(Span::styled(
+                    format!("Last train in {} min!", m),
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                )),
+                None if direction.last.is_some() => spans.push(Span::styled(
+                    "Last train has left",
+                    Style::default().fg(Color::DarkGray),
+                )),
+                _ => {}
+            }
+            text.extend(Text::from(Spans::from(spans)));
+
+            for band in &direction.bands {
+                let frequency = match &band.frequency {
+                    Some(fr) => format!("every {}-{} min", fr.lowestFrequency, fr.highestFrequency),
+                    None => band.kind.clone(),
+                };
+                text.extend(Text::from(Spans::from(Span::styled(
+                    format!("    {}-{}  {}", band.fromTime.label(), band.toTime.label(), frequency),
+                    Style::default().fg(Color::Gray),
+                ))));
+            }
+        }
+    }
+
+    f.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }).block(block), area);
+}
+
+fn draw_suggestions<B: Backend>(f: &mut Frame<B>, app: &App, input: Rect, below: Rect) {
+    if app.suggestions.is_empty() {
+        return;
+    }
+
+    let area = Rect {
+        x: input.x,
+        y: below.y,
+        width: input.width.min(60),
+        height: (app.suggestions.len() as u16 + 2).min(10).min(below.height),
+    };
+
+    let items = app.suggestions
+        .iter()
+        .map(|s| ListItem::new(s.label()))
+        .collect::<Vec<_>>();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(Color::Cyan)),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD).bg(Color::DarkGray));
+
+    let mut state = ListState::default();
+    state.select(app.suggestion_selected);
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut state);
 }
 
 fn draw_input<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
@@ -309,52 +934,245 @@ fn draw_input<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
     }
 }
 
-fn draw_messages<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+fn draw_lines<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let items = app
+        .lineData
+        .iter()
+        .map(|l| match app.config.is_watched(&l.id) {
+            true => format!("★ {}", l.name),
+            false => format!("  {}", l.name),
+        })
+        .map(ListItem::new)
+        .collect::<Vec<_>>();
+    app.lines_tree_size = Some(items.len());
+
+    let lines = List::new(items)
+        .block(
+            Block::default()
+                .title("Lines")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded),
+        )
+        .style(match app.focus {
+            Some(Focus::LinesBlock) => Style::default().fg(Color::Red),
+            _ => Style::default().fg(Color::White),
+        })
+        .highlight_style(Style::default().add_modifier(Modifier::ITALIC))
+        .highlight_symbol(" ");
+
+    let mut state = ListState::default();
+    state.select(app.line_selected);
+    f.render_stateful_widget(lines, area, &mut state)
+}
+
+fn draw_line_detail<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let detail = match &app.this_LineDetail {
+        Some(d) => d,
+        None => {
+            let hint = Paragraph::new(Spans::from(vec![
+                Span::styled("Press ", Style::default().fg(Color::DarkGray)),
+                Span::styled("l", Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled(" to browse lines, ", Style::default().fg(Color::DarkGray)),
+                Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled(" to open one, ", Style::default().fg(Color::DarkGray)),
+                Span::styled("w", Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled(" to watch it.", Style::default().fg(Color::DarkGray)),
+            ]))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .title("Line"),
+            );
+            f.render_widget(hint, area);
+            return;
+        }
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .title(Span::styled(detail.line.name.clone(), Style::default().fg(app.palette.line(&detail.line.id)).add_modifier(Modifier::BOLD)));
+    f.render_widget(block, area);
+
+    // split into status / disruptions / stations and trains
     let chunks = Layout::default()
-        .constraints([Constraint::Percentage(100)])
         .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(5), Constraint::Length(7), Constraint::Min(0)].as_ref())
         .split(area);
 
+    f.render_widget(
+        Paragraph::new(status_text(app, &detail.line))
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .title("Status")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            ),
+        chunks[0],
+    );
+
+    let disruptions = match detail.disruptions.len() {
+        0 => "No disruptions".to_string(),
+        _ => detail.disruptions
+            .iter()
+            .map(|d| format!("[{}] {}", d.categoryDescription, d.description))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+    f.render_widget(
+        Paragraph::new(disruptions)
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .title("Disruptions")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            ),
+        chunks[1],
+    );
+
     {
         let chunks = Layout::default()
-            .constraints([Constraint::Percentage(100)])
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+            .split(chunks[2]);
+
+        let headways = app.line_feeds.get(&detail.line.id).map(headway::analyse).unwrap_or_default();
+        let headway_lines = headways.iter().map(|d| 1 + d.gaps.len().max(1)).sum::<usize>();
+        let left = Layout::default()
             .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(headway_lines as u16 + 2)].as_ref())
             .split(chunks[0]);
 
-        let all_item = ListItem::new("All");
-        let mut items = app
-            .lineNames
+        let stations = detail.stations
             .iter()
-            .map(String::from)
-            .map(ListItem::new)
+            .map(|s| ListItem::new(s.commonName.clone()))
             .collect::<Vec<_>>();
-        items.push(all_item);
-        app.lines_tree_size = Some(items.len());
+        f.render_widget(
+            List::new(stations).block(
+                Block::default()
+                    .title(format!("Stations ({})", detail.stations.len()))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            ),
+            left[0],
+        );
+        draw_headways(f, &headways, left[1]);
 
-        let lines = List::new(items)
-            .block(
+        draw_line_feed(f, app, &detail.line.id, chunks[1]);
+    }
+}
+
+// per direction, the usual wait between trains and any gaps well beyond it
+fn draw_headways<B: Backend>(f: &mut Frame<B>, headways: &[headway::DirectionHeadways], area: Rect) {
+    let minutes = |secs: Option<i32>| match secs {
+        Some(s) => format!("{:.1} min", s as f64 / 60.0),
+        None => "-".to_string(),
+    };
+    let mut lines = Vec::new();
+    for direction in headways {
+        lines.push(Spans::from(vec![
+            Span::styled(direction.direction.clone(), Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(format!(
+                " {} trains, every {}, longest {}",
+                direction.trains,
+                minutes(direction.median_secs),
+                minutes(direction.longest_secs),
+            )),
+        ]));
+        match direction.gaps.is_empty() {
+            true => lines.push(Spans::from(Span::styled("  no unusual gaps", Style::default().fg(Color::DarkGray)))),
+            false => lines.extend(direction.gaps.iter().map(|g| Spans::from(Span::styled(format!("  {}", g.label()), Style::default().fg(Color::LightRed))))),
+        }
+    }
+    f.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title("Headways")
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded),
+        ),
+        area,
+    );
+}
+
+// every train on the line, per direction: where it is, the stop it reaches next and where it is going
+fn draw_line_feed<B: Backend>(f: &mut Frame<B>, app: &App, line: &str, area: Rect) {
+    let feed = match app.line_feeds.get(line) {
+        Some(feed) if !(feed.failed && feed.arrivals.is_empty()) => feed,
+        feed => {
+            f.render_widget(
                 Block::default()
-                    .title("Lines")
-                    .title_alignment(Alignment::Center)
+                    .title(match feed {
+                        Some(_) => "Live trains (failed to load, retrying)",
+                        None => "Live trains (loading)",
+                    })
                     .borders(Borders::ALL)
                     .border_type(BorderType::Rounded),
-            )
-            .style(match app.focus {
-                Some(Focus::LinesBlock) => Style::default().fg(Color::Red),
-                _ => Style::default().fg(Color::White),
-            })
-            .highlight_style(Style::default().add_modifier(Modifier::ITALIC))
-            .highlight_symbol(" ");
-
-        let mut state = ListState::default();
-        state.select(app.line_selected);
-        f.render_stateful_widget(lines, area, &mut state)
+                area,
+            );
+            return;
+        }
+    };
+
+    let directions = feed.by_direction();
+    let count = directions.len().max(1);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Ratio(1, count as u32); count])
+        .split(area);
+
+    for (i, (direction, trains)) in directions.iter().enumerate() {
+        let items = trains
+            .iter()
+            .map(|a| ListItem::new(format!(
+                "{} ---- next {} {} (to {})",
+                match a.currentLocation.is_empty() {
+                    true => "Unknown location",
+                    false => &a.currentLocation,
+                },
+                a.stationName.trim_end_matches(" Underground Station"),
+                a.display_time(app.config.time_format),
+                a.destination(),
+            )))
+            .collect::<Vec<_>>();
+        f.render_widget(
+            List::new(items).block(
+                Block::default()
+                    .title(format!("{} ({} trains, {}s ago)", direction, trains.len(), feed.refreshed_at.elapsed().as_secs()))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            ),
+            chunks[i],
+        );
+    }
+    if directions.is_empty() {
+        f.render_widget(
+            Paragraph::new("No trains predicted").block(
+                Block::default()
+                    .title("Live trains")
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            ),
+            chunks[0],
+        );
     }
 }
 
 fn draw_dashboard<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
     let block = Block::default()
-        .title("Dashboard")
+        .title(format!(
+            "Dashboard ({}{})",
+            app.dashboard_sort.label(),
+            match app.disrupted_only {
+                true => ", disrupted only",
+                false => "",
+            }
+        ))
         .title_alignment(Alignment::Center)
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::White))
@@ -362,88 +1180,73 @@ fn draw_dashboard<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
         .style(Style::default());
     f.render_widget(block, area);
 
+    // split into card grid and legend, the legend as tall as its entries wrap to
+    let legend = legend_entries(app);
+    let (area, legend_area) = {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([Constraint::Min(0), Constraint::Length(1 + legend_rows(&legend, area.width.saturating_sub(2)))].as_ref())
+            .split(area);
+        (chunks[0], chunks[1])
+    };
+    draw_legend(f, app, &legend, legend_area);
+
+    let lines = app.dashboard_lines();
+    if lines.is_empty() {
+        f.render_widget(
+            Paragraph::new("No disrupted lines").alignment(Alignment::Center),
+            area,
+        );
+        return;
+    }
+
+    // one row per three cards
+    let cols = 3;
+    let row_count = lines.len().div_ceil(cols);
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .margin(2)
-        .constraints(
-            [
-                Constraint::Percentage(33),
-                Constraint::Percentage(33),
-                Constraint::Percentage(33),
-            ]
-            .as_ref(),
-        )
+        .margin(1)
+        .constraints(vec![Constraint::Ratio(1, row_count as u32); row_count])
         .split(area);
 
-    let mut q = app.lineData.clone();
-
     // create rows
     let mut rows: Vec<Vec<Rect>> = Vec::new();
-    for i in 0..3 {
+    for chunk in chunks.iter().take(row_count) {
         rows.push(
             Layout::default()
                 .direction(Direction::Horizontal)
-                .constraints(
-                    [
-                        Constraint::Percentage(33),
-                        Constraint::Percentage(33),
-                        Constraint::Percentage(33),
-                    ]
-                    .as_ref(),
-                )
-                .split(chunks[i]),
+                .constraints(vec![Constraint::Ratio(1, cols as u32); cols])
+                .split(*chunk),
         );
     }
 
     // populate grid
-    for x in 0..3 {
-        for y in 0..3 {
-            let item = q.pop().unwrap();
+    for (i, item) in lines.into_iter().enumerate() {
+        let (x, y) = (i / cols, i % cols);
+        f.render_widget(
+            Block::default()
+                .title(Span::styled(
+                    match app.config.is_watched(&item.id) {
+                        true => format!("★ {}", item.name),
+                        false => item.name.clone(),
+                    },
+                    Style::default().fg(app.palette.line(&item.id)).add_modifier(Modifier::BOLD),
+                ))
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(app.palette.severity(item.worst_severity()))),
+            rows[x][y],
+        );
+        {
+            let chunks = Layout::default()
+                .margin(1)
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(100)].as_ref())
+                .split(rows[x][y]);
+
             f.render_widget(
-                Block::default()
-                    .title(item.name)
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded)
-                    .border_style(Style::default().fg(match &item.lineStatuses[0] {
-                        Some(s) => {
-                            if s.statusSeverity != 10 {
-                                Color::LightRed
-                            } else {
-                                Color::LightGreen
-                            }
-                        }
-                        _ => Color::LightGreen,
-                    })),
-                rows[x][y],
-            );
-            {
-                let chunks = Layout::default()
-                    .margin(1)
-                    .direction(Direction::Vertical)
-                    .constraints(match &item.lineStatuses[0] {
-                        Some(s) => match &s.reason {
-                            Some(r) => {
-                                [Constraint::Percentage(30), Constraint::Percentage(30)]
-                                    .as_ref()
-                            }
-                            None => {
-                                [Constraint::Percentage(20), Constraint::Percentage(20)]
-                                    .as_ref()
-                            }
-                        },
-                        None => [Constraint::Percentage(20), Constraint::Percentage(20)]
-                            .as_ref(),
-                    })
-                    .split(rows[x][y]);
-
-                f.render_widget(
-                    Paragraph::new(match &item.lineStatuses[0] {
-                        Some(s) => match &s.reason {
-                            Some(r) => r,
-                            None => "Good Service",
-                        },
-                        None => "No LineStatus",
-                    })
+                Paragraph::new(status_text(app, &item))
                     .style(Style::default())
                     .wrap(Wrap { trim: true })
                     .block(
@@ -452,9 +1255,87 @@ fn draw_dashboard<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
                             .borders(Borders::ALL)
                             .border_type(BorderType::Rounded),
                     ),
-                    chunks[0],
-                );
-            }
+                chunks[0],
+            );
         }
     }
-}
\ No newline at end of file
+}
+
+// every status on a line, worst first, each with its reason and validity window
+fn status_text(app: &App, line: &Line) -> Text<'static> {
+    let mut statuses = line.lineStatuses.iter().flatten().collect::<Vec<_>>();
+    if statuses.is_empty() {
+        return Text::from("No LineStatus");
+    }
+    statuses.sort_by_key(|s| s.severity());
+
+    let mut text = Text::default();
+    for status in statuses {
+        text.extend(Text::from(Spans::from(Span::styled(
+            status.statusSeverityDescription.clone(),
+            Style::default().fg(app.palette.severity(status.severity())).add_modifier(Modifier::BOLD),
+        ))));
+        if let Some(reason) = &status.reason {
+            text.extend(Text::raw(reason.clone()));
+        }
+        for period in &status.validityPeriods {
+            text.extend(Text::from(Spans::from(Span::styled(
+                period.window(),
+                Style::default().fg(Color::DarkGray),
+            ))));
+        }
+    }
+    text
+}
+
+// the severities of the lines loaded right now, worst first; listing all of them would not fit
+fn legend_entries(app: &App) -> Vec<(Severity, String)> {
+    let mut severities = app.lineData
+        .iter()
+        .flat_map(|l| l.lineStatuses.iter().flatten())
+        .map(|s| s.severity())
+        .collect::<Vec<_>>();
+    severities.sort();
+    severities.dedup();
+    severities
+        .into_iter()
+        .map(|s| (s, app.severities.get(&s.level()).cloned().unwrap_or_else(|| s.description())))
+        .collect()
+}
+
+// rows the legend takes at this width, wrapping between entries
+fn legend_rows(entries: &[(Severity, String)], width: u16) -> u16 {
+    let mut rows = 1;
+    let mut used = 0;
+    for (_, description) in entries {
+        let entry = 2 + description.width() + 2;
+        if used > 0 && used + entry > width as usize {
+            rows += 1;
+            used = 0;
+        }
+        used += entry;
+    }
+    rows
+}
+
+fn draw_legend<B: Backend>(f: &mut Frame<B>, app: &App, entries: &[(Severity, String)], area: Rect) {
+    let spans = entries
+        .iter()
+        .flat_map(|(s, description)| vec![
+            Span::styled("■ ", Style::default().fg(app.palette.severity(*s))),
+            Span::raw(format!("{}  ", description)),
+        ])
+        .collect::<Vec<_>>();
+
+    f.render_widget(
+        Paragraph::new(Spans::from(spans))
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .title("Legend")
+                    .borders(Borders::TOP)
+                    .border_type(BorderType::Rounded),
+            ),
+        area,
+    );
+}